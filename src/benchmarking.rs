@@ -0,0 +1,411 @@
+//! Benchmarking setup for pallet-subscription
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as PalletSubscription;
+use frame_benchmarking::{account, benchmarks, vec, whitelisted_caller, Vec};
+use frame_support::{
+	sp_runtime::traits::{Bounded, One, Zero},
+	traits::{Get, Hooks},
+	BoundedVec,
+};
+use frame_system::RawOrigin;
+
+const SEED: u32 = 0;
+
+fn funded_account<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+	let account: T::AccountId = account(name, index, SEED);
+	T::Currency::make_free_balance_be(&account, BalanceOf::<T>::max_value() / 2u32.into());
+	account
+}
+
+fn funded_caller<T: Config>() -> T::AccountId {
+	let caller: T::AccountId = whitelisted_caller();
+	T::Currency::make_free_balance_be(&caller, BalanceOf::<T>::max_value() / 2u32.into());
+	caller
+}
+
+fn instalment_amount<T: Config>() -> BalanceOf<T> {
+	T::Currency::minimum_balance().max(1u32.into())
+}
+
+fn next_block<T: Config>() -> T::BlockNumber {
+	frame_system::Pallet::<T>::block_number() + One::one()
+}
+
+benchmarks! {
+	// Prepaid, the instalments are reserved along with the deposit
+	subscribe {
+		let caller = funded_caller::<T>();
+		let beneficiary = funded_account::<T>("beneficiary", 0);
+		let amount = instalment_amount::<T>();
+	}: _(
+		RawOrigin::Signed(caller.clone()),
+		beneficiary,
+		amount,
+		10u32.into(),
+		Some(10),
+		None,
+		ExistencePolicy::KeepAlive,
+		None,
+		true
+	)
+	verify {
+		assert!(SubscriptionsById::<T>::contains_key(0));
+		assert_eq!(
+			T::Currency::reserved_balance(&caller),
+			T::SubscriptionDeposit::get() + amount * 10u32.into()
+		);
+	}
+
+	unsubscribe {
+		let caller = funded_caller::<T>();
+		let beneficiary = funded_account::<T>("beneficiary", 0);
+		let amount = instalment_amount::<T>();
+		PalletSubscription::<T>::subscribe(
+			RawOrigin::Signed(caller.clone()).into(),
+			beneficiary,
+			amount,
+			10u32.into(),
+			Some(10),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false,
+		)?;
+	}: _(RawOrigin::Signed(caller), next_block::<T>(), 0)
+	verify {
+		assert_eq!(SubscriptionsById::<T>::get(0).unwrap().status, SubscriptionStatus::Cancelled);
+	}
+
+	unsubscribe_by_id {
+		let caller = funded_caller::<T>();
+		let beneficiary = funded_account::<T>("beneficiary", 0);
+		let amount = instalment_amount::<T>();
+		PalletSubscription::<T>::subscribe(
+			RawOrigin::Signed(caller.clone()).into(),
+			beneficiary,
+			amount,
+			10u32.into(),
+			Some(10),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false,
+		)?;
+	}: _(RawOrigin::Signed(caller), 0)
+	verify {
+		assert_eq!(SubscriptionsById::<T>::get(0).unwrap().status, SubscriptionStatus::Cancelled);
+	}
+
+	create_plan {
+		let m in 1 .. T::MaxMetadataLength::get();
+
+		let caller = funded_caller::<T>();
+		let amount = instalment_amount::<T>();
+	}: _(
+		RawOrigin::Signed(caller.clone()),
+		amount,
+		10u32.into(),
+		Some(10),
+		0u32.into(),
+		vec![0u8; m as usize]
+	)
+	verify {
+		assert!(Plans::<T>::contains_key(0));
+		assert_eq!(T::Currency::reserved_balance(&caller), T::PlanDeposit::get());
+	}
+
+	subscribe_to_plan {
+		let caller = funded_caller::<T>();
+		let owner = funded_account::<T>("owner", 0);
+		let amount = instalment_amount::<T>();
+		PalletSubscription::<T>::create_plan(
+			RawOrigin::Signed(owner).into(),
+			amount,
+			10u32.into(),
+			Some(10),
+			0u32.into(),
+			Vec::new(),
+		)?;
+	}: _(RawOrigin::Signed(caller), 0)
+	verify {
+		assert!(SubscriptionsById::<T>::contains_key(0));
+	}
+
+	set_plan_metadata {
+		let m in 1 .. T::MaxMetadataLength::get();
+
+		let caller = funded_caller::<T>();
+		let amount = instalment_amount::<T>();
+		PalletSubscription::<T>::create_plan(
+			RawOrigin::Signed(caller.clone()).into(),
+			amount,
+			10u32.into(),
+			Some(10),
+			0u32.into(),
+			Vec::new(),
+		)?;
+	}: _(RawOrigin::Signed(caller), 0, vec![0u8; m as usize])
+	verify {
+		assert_eq!(PlanMetadata::<T>::get(0).unwrap().len(), m as usize);
+	}
+
+	// Every instalment is rescheduled, which is the most expensive path
+	on_initialize {
+		let s in 0 .. T::MaxSubscriptionsPerBlock::get();
+
+		let amount = instalment_amount::<T>();
+		for i in 0 .. s {
+			let payer = funded_account::<T>("payer", i);
+			let beneficiary = funded_account::<T>("beneficiary", i);
+			PalletSubscription::<T>::subscribe(
+				RawOrigin::Signed(payer).into(),
+				beneficiary,
+				amount,
+				10u32.into(),
+				None,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false,
+			)?;
+		}
+		let when = next_block::<T>();
+	}: {
+		PalletSubscription::<T>::on_initialize(when);
+	}
+	verify {
+		assert!(Subscriptions::<T>::get(when).is_empty());
+		assert_eq!(Subscriptions::<T>::get(when + 10u32.into()).len(), s as usize);
+	}
+
+	// Every block looked at before the last one is full
+	schedule_subscription {
+		let l in 0 .. T::MaxScheduleLookahead::get();
+
+		let when = next_block::<T>();
+		let full: Vec<SubscriptionId> = (0 .. T::MaxSubscriptionsPerBlock::get() as u64).collect();
+		let full: BoundedVec<_, _> = full.try_into().map_err(|_| "block is over the limit")?;
+		for offset in 0 .. l {
+			Subscriptions::<T>::insert(when + offset.into(), full.clone());
+		}
+	}: {
+		PalletSubscription::<T>::schedule_subscription(when, SubscriptionId::MAX)?;
+	}
+	verify {
+		assert!(Subscriptions::<T>::get(when + l.into()).contains(&SubscriptionId::MAX));
+	}
+
+	// The instalment is paid and rescheduled, which is the most expensive path
+	execute_overdue {
+		let caller = funded_caller::<T>();
+		let payer = funded_account::<T>("payer", 0);
+		let beneficiary = funded_account::<T>("beneficiary", 0);
+		let amount = instalment_amount::<T>();
+		PalletSubscription::<T>::subscribe(
+			RawOrigin::Signed(payer).into(),
+			beneficiary,
+			amount,
+			10u32.into(),
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false,
+		)?;
+		let when = next_block::<T>();
+		frame_system::Pallet::<T>::set_block_number(when + One::one());
+	}: _(RawOrigin::Signed(caller), 0)
+	verify {
+		assert!(Subscriptions::<T>::get(when).is_empty());
+		assert_eq!(Subscriptions::<T>::get(when + 10u32.into()).to_vec(), vec![0]);
+	}
+
+	pause {
+		let caller = funded_caller::<T>();
+		let beneficiary = funded_account::<T>("beneficiary", 0);
+		let amount = instalment_amount::<T>();
+		PalletSubscription::<T>::subscribe(
+			RawOrigin::Signed(caller.clone()).into(),
+			beneficiary,
+			amount,
+			10u32.into(),
+			Some(10),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false,
+		)?;
+	}: _(RawOrigin::Signed(caller), 0)
+	verify {
+		assert_eq!(SubscriptionsById::<T>::get(0).unwrap().status, SubscriptionStatus::Paused);
+	}
+
+	resume {
+		let caller = funded_caller::<T>();
+		let beneficiary = funded_account::<T>("beneficiary", 0);
+		let amount = instalment_amount::<T>();
+		PalletSubscription::<T>::subscribe(
+			RawOrigin::Signed(caller.clone()).into(),
+			beneficiary,
+			amount,
+			10u32.into(),
+			Some(10),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false,
+		)?;
+		PalletSubscription::<T>::pause(RawOrigin::Signed(caller.clone()).into(), 0)?;
+	}: _(RawOrigin::Signed(caller), 0)
+	verify {
+		assert_eq!(SubscriptionsById::<T>::get(0).unwrap().status, SubscriptionStatus::Active);
+	}
+
+	refund {
+		let payer = funded_account::<T>("payer", 0);
+		let caller = funded_caller::<T>();
+		let amount = instalment_amount::<T>();
+		PalletSubscription::<T>::subscribe(
+			RawOrigin::Signed(payer).into(),
+			caller.clone(),
+			amount,
+			10u32.into(),
+			Some(10),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false,
+		)?;
+		PalletSubscription::<T>::on_initialize(next_block::<T>());
+	}: _(RawOrigin::Signed(caller), 0, amount)
+	verify {
+		assert_eq!(SubscriptionsById::<T>::get(0).unwrap().refunded, amount);
+	}
+
+	propose_amendment {
+		let caller = funded_caller::<T>();
+		let beneficiary = funded_account::<T>("beneficiary", 0);
+		let amount = instalment_amount::<T>();
+		PalletSubscription::<T>::subscribe(
+			RawOrigin::Signed(caller.clone()).into(),
+			beneficiary,
+			amount,
+			10u32.into(),
+			Some(10),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false,
+		)?;
+	}: _(RawOrigin::Signed(caller), 0, amount * 2u32.into(), 10u32.into(), Some(10))
+	verify {
+		assert!(Amendments::<T>::contains_key(0));
+	}
+
+	// The prepaid reserve is topped up, which is the most expensive path
+	accept_amendment {
+		let caller = funded_caller::<T>();
+		let beneficiary = funded_account::<T>("beneficiary", 0);
+		let amount = instalment_amount::<T>();
+		PalletSubscription::<T>::subscribe(
+			RawOrigin::Signed(caller.clone()).into(),
+			beneficiary.clone(),
+			amount,
+			10u32.into(),
+			Some(10),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			true,
+		)?;
+		PalletSubscription::<T>::propose_amendment(
+			RawOrigin::Signed(beneficiary).into(),
+			0,
+			amount * 2u32.into(),
+			10u32.into(),
+			Some(10),
+		)?;
+	}: _(RawOrigin::Signed(caller.clone()), 0, amount * 2u32.into(), 10u32.into(), Some(10))
+	verify {
+		assert!(!Amendments::<T>::contains_key(0));
+		assert_eq!(
+			T::Currency::reserved_balance(&caller),
+			T::SubscriptionDeposit::get() + amount * 20u32.into()
+		);
+	}
+
+	// A subscriber is left to migrate, which is the most expensive path
+	update_plan_price {
+		let caller = funded_caller::<T>();
+		let subscriber = funded_account::<T>("subscriber", 0);
+		let amount = instalment_amount::<T>();
+		PalletSubscription::<T>::create_plan(
+			RawOrigin::Signed(caller.clone()).into(),
+			amount,
+			10u32.into(),
+			Some(10),
+			0u32.into(),
+			Vec::new(),
+		)?;
+		PalletSubscription::<T>::subscribe_to_plan(RawOrigin::Signed(subscriber).into(), 0)?;
+	}: _(RawOrigin::Signed(caller), 0, amount * 2u32.into(), PriceUpdateMode::Migrate)
+	verify {
+		assert!(PlanPriceMigrations::<T>::contains_key(0));
+	}
+
+	reap {
+		let caller = funded_caller::<T>();
+		let beneficiary = funded_account::<T>("beneficiary", 0);
+		let amount = instalment_amount::<T>();
+		PalletSubscription::<T>::subscribe(
+			RawOrigin::Signed(caller.clone()).into(),
+			beneficiary,
+			amount,
+			10u32.into(),
+			Some(1),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false,
+		)?;
+		let when = next_block::<T>();
+		PalletSubscription::<T>::on_initialize(when);
+		frame_system::Pallet::<T>::set_block_number(when + T::ArchivePeriod::get());
+	}: _(RawOrigin::Signed(caller.clone()), 0)
+	verify {
+		assert!(!SubscriptionsById::<T>::contains_key(0));
+		assert!(T::Currency::reserved_balance(&caller).is_zero());
+	}
+
+	remove_plan {
+		let t in 0 .. T::MaxTrialsRemoved::get();
+
+		let caller = funded_caller::<T>();
+		let amount = instalment_amount::<T>();
+		PalletSubscription::<T>::create_plan(
+			RawOrigin::Signed(caller.clone()).into(),
+			amount,
+			10u32.into(),
+			Some(10),
+			0u32.into(),
+			vec![0u8; T::MaxMetadataLength::get() as usize],
+		)?;
+		for i in 0 .. t {
+			TrialsUsed::<T>::insert(0, account::<T::AccountId>("payer", i, SEED), ());
+		}
+	}: _(RawOrigin::Signed(caller.clone()), 0)
+	verify {
+		assert!(!Plans::<T>::contains_key(0));
+		assert_eq!(TrialsUsed::<T>::iter_prefix(0).count(), 0);
+		assert!(T::Currency::reserved_balance(&caller).is_zero());
+	}
+
+	impl_benchmark_test_suite!(
+		PalletSubscription,
+		crate::tests::mock::ExternalityBuilder::default().build(),
+		crate::tests::mock::TestRuntime
+	);
+}
@@ -3,18 +3,24 @@
 
 pub use pallet::*;
 
+pub mod migrations;
 pub mod types;
+pub mod weights;
 
 pub use types::*;
+pub use weights::*;
 
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
 pub use frame_support::{
 	storage::IterableStorageMap,
 	traits::tokens::{
 		currency::{Currency, ReservableCurrency},
-		ExistenceRequirement,
+		fungibles, BalanceStatus, ExistenceRequirement,
 	},
 	ReversibleStorageHasher,
 };
@@ -23,7 +29,11 @@ pub use frame_support::{
 pub mod pallet {
 
 	use super::*;
-	use frame_support::{pallet_prelude::*, sp_runtime::traits::Zero};
+	use frame_support::{
+		pallet_prelude::*,
+		sp_runtime::{traits::Zero, Perbill, TransactionOutcome},
+		storage::with_transaction,
+	};
 	use frame_system::pallet_prelude::*;
 
 	#[pallet::config]
@@ -33,7 +43,9 @@ pub mod pallet {
 		/// The trait to manage funds
 		type Currency: Currency<Self::AccountId> + ReservableCurrency<Self::AccountId>;
 		/// Weight information for extrinsics in this pallet.
-		// type WeightInfo: WeightInfo;
+		type WeightInfo: WeightInfo;
+		/// The fungible assets subscriptions can be paid in, besides the native currency
+		type Assets: fungibles::Transfer<Self::AccountId, Balance = BalanceOf<Self>>;
 		/// The maximum amount of metadata
 		#[pallet::constant]
 		type MaxMetadataLength: Get<u32>;
@@ -41,10 +53,54 @@ pub mod pallet {
 		/// priority than `schedule::HARD_DEADLINE`.
 		#[pallet::constant]
 		type MaximumWeight: Get<Weight>;
+		/// How many times a failed instalment is retried before the subscription gets cancelled
+		#[pallet::constant]
+		type MaxRetries: Get<u32>;
+		/// The number of blocks to wait before retrying a failed instalment
+		#[pallet::constant]
+		type RetryInterval: Get<Self::BlockNumber>;
+		/// The maximum number of instalments that can be scheduled in a single block, the
+		/// following ones overflow to the next block with some room left
+		#[pallet::constant]
+		type MaxSubscriptionsPerBlock: Get<u32>;
+		/// How many blocks past the one it is due at an instalment can overflow to, scheduling
+		/// fails once they are all full
+		#[pallet::constant]
+		type MaxScheduleLookahead: Get<u32>;
+		/// The share of an overdue instalment the beneficiary pays to whoever executes it with
+		/// `execute_overdue`
+		#[pallet::constant]
+		type KeeperRewardShare: Get<Perbill>;
+		/// The amount reserved from the payer for every subscription, given back once the
+		/// subscription is reaped
+		#[pallet::constant]
+		type SubscriptionDeposit: Get<BalanceOf<Self>>;
+		/// The amount reserved from the owner of every plan, given back once the plan is removed
+		#[pallet::constant]
+		type PlanDeposit: Get<BalanceOf<Self>>;
+		/// The maximum number of trial records removed along with a plan, the ones past it are
+		/// left in storage
+		#[pallet::constant]
+		type MaxTrialsRemoved: Get<u32>;
+		/// How many blocks ahead the first instalment of a subscription can be
+		#[pallet::constant]
+		type MaxStartDelay: Get<Self::BlockNumber>;
+		/// The number of blocks subscribers are given to cancel before a plan price change
+		/// applies to them
+		#[pallet::constant]
+		type PriceChangeNotice: Get<Self::BlockNumber>;
+		/// How long a subscription that is over is kept on record, e.g. for refunds, before
+		/// either party can reap it
+		#[pallet::constant]
+		type ArchivePeriod: Get<Self::BlockNumber>;
 	}
 
+	/// Version 1 gives every subscription an id, see `migrations::v1`
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::storage]
@@ -57,30 +113,213 @@ pub mod pallet {
 		_,
 		Twox64Concat,
 		PlanId,
-		InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId>,
+		Plan<T::BlockNumber, BalanceOf<T>, T::AccountId>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn plan_metadata)]
+	pub type PlanMetadata<T: Config> =
+		StorageMap<_, Twox64Concat, PlanId, BoundedVec<u8, T::MaxMetadataLength>, OptionQuery>;
+
+	/// The payers who already had the trial of a plan
+	#[pallet::storage]
+	#[pallet::getter(fn trials_used)]
+	pub type TrialsUsed<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, PlanId, Blake2_128Concat, T::AccountId, (), OptionQuery>;
+
+	/// Price changes of a plan that apply to its existing subscribers once the notice is over
+	#[pallet::storage]
+	#[pallet::getter(fn plan_price_migrations)]
+	pub type PlanPriceMigrations<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		PlanId,
+		PriceMigration<T::BlockNumber, BalanceOf<T>>,
 		OptionQuery,
 	>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn price_migration_nonce)]
+	pub type PriceMigrationNonce<T: Config> = StorageValue<_, PriceMigrationId, ValueQuery>;
+
+	/// Number of ongoing subscriptions made from a plan
+	#[pallet::storage]
+	#[pallet::getter(fn plan_subscription_count)]
+	pub type PlanSubscriptionCount<T: Config> =
+		StorageMap<_, Twox64Concat, PlanId, u32, ValueQuery>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn subscription_nonce)]
 	pub type SubscriptionNonce<T: Config> = StorageValue<_, SubscriptionId, ValueQuery>;
 
 	#[pallet::storage]
-	#[pallet::unbounded]
+	#[pallet::getter(fn subscriptions_by_id)]
+	pub type SubscriptionsById<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		SubscriptionId,
+		InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId, AssetIdOf<T>>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	#[pallet::getter(fn amendments)]
+	pub type Amendments<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		SubscriptionId,
+		Amendment<T::BlockNumber, BalanceOf<T>, T::AccountId>,
+		OptionQuery,
+	>;
+
+	/// The subscriptions of every payer, listed with `subscriptions_by_payer`
+	#[pallet::storage]
+	pub type SubscriptionsByPayer<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Twox64Concat,
+		SubscriptionId,
+		(),
+		OptionQuery,
+	>;
+
+	/// The subscriptions of every beneficiary, listed with `subscriptions_by_beneficiary`
+	#[pallet::storage]
+	pub type SubscriptionsByBeneficiary<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Twox64Concat,
+		SubscriptionId,
+		(),
+		OptionQuery,
+	>;
+
+	/// The oldest block whose instalments have not all been executed yet
+	#[pallet::storage]
+	#[pallet::getter(fn incomplete_since)]
+	pub type IncompleteSince<T: Config> = StorageValue<_, T::BlockNumber, OptionQuery>;
+
+	#[pallet::storage]
 	#[pallet::getter(fn subscriptions)]
 	pub type Subscriptions<T: Config> = StorageMap<
 		_,
 		Twox64Concat,
 		T::BlockNumber,
-		Vec<InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId>>,
+		BoundedVec<SubscriptionId, T::MaxSubscriptionsPerBlock>,
 		ValueQuery,
 	>;
 
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
-		Subscription(InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId>),
-		Unsubscription(InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId>),
+		Subscription(
+			SubscriptionId,
+			InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId, AssetIdOf<T>>,
+		),
+		Unsubscription(
+			SubscriptionId,
+			InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId, AssetIdOf<T>>,
+		),
+		PlanCreated(PlanId, Plan<T::BlockNumber, BalanceOf<T>, T::AccountId>),
+		PlanMetadataSet(PlanId, BoundedVec<u8, T::MaxMetadataLength>),
+		PaymentFailed {
+			subscription_id: SubscriptionId,
+			error: DispatchError,
+		},
+		PaymentRetryScheduled {
+			subscription_id: SubscriptionId,
+			attempt: u32,
+			retry_at: T::BlockNumber,
+		},
+		PaymentRecovered {
+			subscription_id: SubscriptionId,
+		},
+		SubscriptionCancelled {
+			subscription_id: SubscriptionId,
+		},
+		PaymentExecuted {
+			subscription_id: SubscriptionId,
+			payer: T::AccountId,
+			beneficiary: T::AccountId,
+			amount: BalanceOf<T>,
+			remaining_payments: Option<u32>,
+			/// The block the next instalment is scheduled in, if any
+			scheduled_at: Option<T::BlockNumber>,
+		},
+		SubscriptionCompleted {
+			subscription_id: SubscriptionId,
+			payer: T::AccountId,
+			beneficiary: T::AccountId,
+		},
+		PaymentDelayed {
+			subscription_id: SubscriptionId,
+			delay: T::BlockNumber,
+		},
+		KeeperRewarded {
+			subscription_id: SubscriptionId,
+			keeper: T::AccountId,
+			reward: BalanceOf<T>,
+		},
+		TrialStarted {
+			plan_id: PlanId,
+			subscription_id: SubscriptionId,
+			payer: T::AccountId,
+			first_charge_at: T::BlockNumber,
+		},
+		SubscriptionPaused {
+			subscription_id: SubscriptionId,
+		},
+		SubscriptionResumed {
+			subscription_id: SubscriptionId,
+			/// The block the next instalment is scheduled in
+			scheduled_at: T::BlockNumber,
+		},
+		SubscriptionTerminated {
+			subscription_id: SubscriptionId,
+			payer: T::AccountId,
+			beneficiary: T::AccountId,
+		},
+		Refunded {
+			subscription_id: SubscriptionId,
+			amount: BalanceOf<T>,
+		},
+		AmendmentProposed {
+			subscription_id: SubscriptionId,
+			amendment: Amendment<T::BlockNumber, BalanceOf<T>, T::AccountId>,
+		},
+		AmendmentAccepted {
+			subscription_id: SubscriptionId,
+			amendment: Amendment<T::BlockNumber, BalanceOf<T>, T::AccountId>,
+		},
+		/// The price of a plan changed, existing subscribers are moved to it at `effective_at`
+		/// in migrate mode
+		PlanPriceUpdated {
+			plan_id: PlanId,
+			old_amount: BalanceOf<T>,
+			new_amount: BalanceOf<T>,
+			mode: PriceUpdateMode,
+			effective_at: Option<T::BlockNumber>,
+		},
+		SubscriptionPriceMigrated {
+			subscription_id: SubscriptionId,
+			plan_id: PlanId,
+			amount: BalanceOf<T>,
+		},
+		/// Every block the instalment could be scheduled in is full, it is left overdue for
+		/// `execute_overdue`
+		InstalmentNotScheduled {
+			subscription_id: SubscriptionId,
+			due: T::BlockNumber,
+		},
+		SubscriptionReaped {
+			subscription_id: SubscriptionId,
+		},
+		PlanRemoved {
+			plan_id: PlanId,
+		},
 	}
 
 	#[pallet::error]
@@ -89,111 +328,143 @@ pub mod pallet {
 		IndexOutOfBounds,
 		NoSubscriptionPlannedAtBlock,
 		CallerIsNotSubscriber,
+		UnknownPlan,
+		NonceOverflow,
+		MetadataTooLong,
+		CallerIsNotPlanOwner,
+		UnknownSubscription,
+		SubscriptionIsCompleted,
+		SubscriptionNotOverdue,
+		StartInThePast,
+		StartTooFarAhead,
+		TrialTooLong,
+		SubscriptionIsPaused,
+		SubscriptionIsNotPaused,
+		CallerIsNotBeneficiary,
+		RefundExceedsPayments,
+		CallerIsNotParty,
+		NoAmendmentProposed,
+		CallerIsProposer,
+		PrepaidInAsset,
+		ScheduleFull,
+		SubscriptionIsNotOver,
+		ArchivePeriodNotOver,
+		SubscriptionIsTerminated,
+		RefundIsZero,
+		AmendmentMismatch,
+		SubscriptionIsDelinquent,
+		SubscriptionIsCancelled,
 	}
 
 	#[pallet::hooks]
 	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
-		fn on_initialize(block_number: T::BlockNumber) -> Weight {
-			let limit = T::MaximumWeight::get();
-			let mut total_weight: Weight = 0;
-
-			let mut scheduled_subscriptions = <Subscriptions<T>>::take(block_number);
-			total_weight += T::DbWeight::get().reads_writes(1 as Weight, 1 as Weight);
-
-			while total_weight < limit {
-				let sub_info = match scheduled_subscriptions.pop() {
-					Some(data) => data,
-					None => return total_weight,
-				};
-
-				let res_transfer = T::Currency::transfer(
-					&sub_info.payer,
-					&sub_info.beneficiary,
-					sub_info.amount,
-					ExistenceRequirement::KeepAlive,
-				);
-				// TODO: benchmark what costs a call to transfer and add it to total_weight
-				// For now let's use this
-				total_weight += T::DbWeight::get().reads_writes(1 as Weight, 1 as Weight);
-
-				// Cases where we don't want to execute another instalment of this subscription
-				if res_transfer.is_err() {
-					continue
-				}
-				if let Some(1) = sub_info.remaining_payments {
-					continue
-				}
+		fn on_runtime_upgrade() -> Weight {
+			migrations::v1::migrate::<T>()
+		}
 
-				match sub_info.remaining_payments {
-					Some(remaining_payments) => {
-						Self::schedule_subscriptions(
-							block_number + sub_info.frequency,
-							&[InstalmentData {
-								remaining_payments: Some(remaining_payments - 1),
-								..sub_info
-							}],
-						);
-					},
-					None => {
-						Self::schedule_subscriptions(
-							block_number + sub_info.frequency,
-							&[sub_info],
-						);
-					},
-				}
-				total_weight += T::DbWeight::get().reads_writes(1 as Weight, 1 as Weight);
-			}
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			// Instalments left over by previous blocks are the first to go
+			let since = <IncompleteSince<T>>::take().unwrap_or(now);
+			// Taking `IncompleteSince` out, and putting it back if the block is left incomplete
+			let since_weight = T::DbWeight::get().reads_writes(1 as Weight, 1 as Weight);
+			Self::execute_scheduled(now, since, T::MaximumWeight::get())
+				.saturating_add(since_weight)
+		}
 
-			if !scheduled_subscriptions.is_empty() {
-				Self::schedule_subscriptions(
-					block_number + T::BlockNumber::from(1u32),
-					&scheduled_subscriptions,
-				);
-				total_weight += T::DbWeight::get().reads_writes(1 as Weight, 1 as Weight);
+		fn on_idle(now: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			// Taking `IncompleteSince` out, and putting it back if the backlog is not drained
+			let since_weight = T::DbWeight::get().reads_writes(1 as Weight, 1 as Weight);
+			let lookahead_weight = Self::schedule_lookahead_weight();
+			if remaining_weight
+				< T::WeightInfo::on_initialize(1)
+					.saturating_add(lookahead_weight)
+					.saturating_add(since_weight)
+			{
+				return 0
 			}
 
-			total_weight
+			let since = match <IncompleteSince<T>>::take() {
+				Some(since) => since,
+				None => return since_weight,
+			};
+			// An instalment is only started while under the limit, keep room for the last one
+			let instalment_weight = T::WeightInfo::on_initialize(1)
+				.saturating_sub(T::WeightInfo::on_initialize(0))
+				.saturating_add(lookahead_weight);
+			Self::execute_scheduled(
+				now,
+				since,
+				remaining_weight.saturating_sub(instalment_weight).saturating_sub(since_weight),
+			)
+			.saturating_add(since_weight)
 		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
-		#[pallet::weight(1_000)]
+		/// Pay `amount` to `to` every `frequency` blocks, starting next block, for
+		/// `number_of_installment` instalments or until unsubscribing when there is no limit.
+		///
+		/// Instalments are paid in `asset`, or in the native currency when there is none. With
+		/// `ExistencePolicy::KeepAlive` an instalment that would take the payer below the
+		/// existential deposit fails and is retried. With `ExistencePolicy::AllowDeath` it goes
+		/// through and may drain the free balance, but the `SubscriptionDeposit` stays reserved
+		/// until the subscription is reaped and keeps the account alive until then. Only with no
+		/// deposit configured can the account itself be reaped.
+		///
+		/// The first instalment is paid at `start_at` when given, which has to be in the future
+		/// and at most `MaxStartDelay` blocks ahead.
+		///
+		/// A `prepaid` subscription reserves all of its instalments from the payer upfront and pays
+		/// them out of the reserve, so it needs a number of instalments and the native currency.
+		#[pallet::weight(
+			T::WeightInfo::subscribe()
+				.saturating_add(Pallet::<T>::schedule_lookahead_weight())
+		)]
+		#[allow(clippy::too_many_arguments)]
 		pub fn subscribe(
 			origin: OriginFor<T>,
 			to: T::AccountId,
 			amount: BalanceOf<T>,
 			frequency: T::BlockNumber,
 			number_of_installment: Option<u32>,
+			asset: Option<AssetIdOf<T>>,
+			existence_policy: ExistencePolicy,
+			start_at: Option<T::BlockNumber>,
+			prepaid: bool,
 		) -> DispatchResult {
 			let from = ensure_signed(origin)?;
 
-			ensure!(
-				!frequency.is_zero()
-					&& !amount.is_zero() && match number_of_installment {
-					Some(x) => x >= 1,
-					None => true,
-				} && to != from,
-				Error::<T>::InvalidSubscription
-			);
-
-			let subscription = InstalmentData {
-				frequency,
-				amount,
-				remaining_payments: number_of_installment,
-				beneficiary: to,
-				payer: from,
+			let now = <frame_system::Pallet<T>>::block_number();
+			let start_at = match start_at {
+				Some(start_at) => {
+					ensure!(start_at > now, Error::<T>::StartInThePast);
+					ensure!(
+						start_at <= now.saturating_add(T::MaxStartDelay::get()),
+						Error::<T>::StartTooFarAhead
+					);
+					start_at
+				},
+				None => now + 1u32.into(),
 			};
 
-			let next_block_number = <frame_system::Pallet<T>>::block_number() + 1u32.into();
-
-			Self::schedule_subscriptions(next_block_number, &[subscription.clone()]);
-
-			Self::deposit_event(Event::Subscription(subscription));
-			Ok(())
+			Self::new_subscription(
+				from,
+				SubscriptionTerms {
+					beneficiary: to,
+					amount,
+					frequency,
+					remaining_payments: number_of_installment,
+					asset,
+					existence_policy,
+					start_at,
+					plan_id: None,
+					prepaid,
+				},
+			)
 		}
 
-		#[pallet::weight(1_000)]
+		#[pallet::weight(T::WeightInfo::unsubscribe())]
 		pub fn unsubscribe(
 			origin: OriginFor<T>,
 			when: T::BlockNumber,
@@ -212,33 +483,1148 @@ pub mod pallet {
 
 			ensure!(index < length, Error::<T>::IndexOutOfBounds,);
 
-			let desired_subscription = &instalments[index];
+			let subscription_id = instalments[index];
+			let subscription_data = Self::subscriptions_by_id(subscription_id)
+				.ok_or(Error::<T>::InvalidSubscription)?;
 
+			let by_beneficiary = subscription_data.beneficiary == from;
 			ensure!(
-				desired_subscription.payer == from,
+				subscription_data.payer == from || by_beneficiary,
 				Error::<T>::CallerIsNotSubscriber,
 			);
 
-			// Those two lines are safe because we checked index < length
-			// Doing this rather than calling remove reduce complexity to 0(1)
-			instalments.swap(index, length - 1);
-			let subscription_data = instalments.pop().unwrap();
+			// Keep the order of the instalments left, they are executed first come first served
+			instalments.remove(index);
 
 			<Subscriptions<T>>::insert(when, instalments);
+			Self::end_subscription(subscription_id, subscription_data, by_beneficiary);
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::unsubscribe_by_id())]
+		pub fn unsubscribe_by_id(
+			origin: OriginFor<T>,
+			subscription_id: SubscriptionId,
+		) -> DispatchResult {
+			let from = ensure_signed(origin)?;
+
+			let subscription_data = Self::subscriptions_by_id(subscription_id)
+				.ok_or(Error::<T>::UnknownSubscription)?;
+
+			// The beneficiary can terminate the subscription as well, e.g. for a discontinued
+			// service
+			let by_beneficiary = subscription_data.beneficiary == from;
+			ensure!(
+				subscription_data.payer == from || by_beneficiary,
+				Error::<T>::CallerIsNotSubscriber,
+			);
+			Self::ensure_ongoing(subscription_data.status)?;
+
+			Self::unschedule_subscription(subscription_data.scheduled_at, subscription_id);
+			Self::end_subscription(subscription_id, subscription_data, by_beneficiary);
+
+			Ok(())
+		}
+
+		/// Execute an instalment that is overdue, because its block ran out of weight or every
+		/// block it could be scheduled in was full. Anyone can call it, and when the payment goes
+		/// through the beneficiary gives `KeeperRewardShare` of the amount paid to the caller,
+		/// unless they are the beneficiary themselves.
+		#[pallet::weight(
+			T::WeightInfo::execute_overdue()
+				.saturating_add(Pallet::<T>::schedule_lookahead_weight())
+		)]
+		pub fn execute_overdue(
+			origin: OriginFor<T>,
+			subscription_id: SubscriptionId,
+		) -> DispatchResult {
+			let keeper = ensure_signed(origin)?;
+
+			let subscription_data = Self::subscriptions_by_id(subscription_id)
+				.ok_or(Error::<T>::UnknownSubscription)?;
+			Self::ensure_ongoing(subscription_data.status)?;
+			ensure!(
+				subscription_data.status != SubscriptionStatus::Paused,
+				Error::<T>::SubscriptionIsPaused,
+			);
+
+			// Instalments of the current block are only overdue once `on_initialize` ran out of
+			// weight before reaching them
+			let now = <frame_system::Pallet<T>>::block_number();
+			let is_overdue = subscription_data.scheduled_at < now
+				|| (subscription_data.scheduled_at == now && Self::incomplete_since().is_some());
+			ensure!(is_overdue, Error::<T>::SubscriptionNotOverdue);
+
+			Self::unschedule_subscription(subscription_data.scheduled_at, subscription_id);
+			// The amount paid may differ from the one read above when a price migration applied
+			let paid = match Self::execute_instalment(now, subscription_id) {
+				Some(paid) => paid,
+				None => return Ok(()),
+			};
+
+			let reward = T::KeeperRewardShare::get() * paid;
+			if reward.is_zero() || keeper == subscription_data.beneficiary {
+				return Ok(())
+			}
+			// The reward is a courtesy of the beneficiary, the payment stands even without it
+			if Self::transfer(
+				subscription_data.asset,
+				&subscription_data.beneficiary,
+				&keeper,
+				reward,
+				ExistenceRequirement::KeepAlive,
+			)
+			.is_ok()
+			{
+				Self::deposit_event(Event::KeeperRewarded {
+					subscription_id,
+					keeper,
+					reward,
+				});
+			}
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::create_plan(metadata.len() as u32))]
+		pub fn create_plan(
+			origin: OriginFor<T>,
+			amount: BalanceOf<T>,
+			frequency: T::BlockNumber,
+			number_of_installment: Option<u32>,
+			trial: T::BlockNumber,
+			metadata: Vec<u8>,
+		) -> DispatchResult {
+			let from = ensure_signed(origin)?;
+
+			ensure!(
+				Self::are_valid_terms(amount, frequency, number_of_installment),
+				Error::<T>::InvalidSubscription
+			);
+			ensure!(trial < T::MaxStartDelay::get(), Error::<T>::TrialTooLong);
+			let metadata: BoundedVec<u8, T::MaxMetadataLength> =
+				metadata.try_into().map_err(|_| Error::<T>::MetadataTooLong)?;
+
+			let plan_id = <PlanNonce<T>>::try_mutate(|nonce| -> Result<PlanId, DispatchError> {
+				let plan_id = *nonce;
+				*nonce = nonce.checked_add(1).ok_or(Error::<T>::NonceOverflow)?;
+				Ok(plan_id)
+			})?;
+
+			// The deposit pays for the storage used by the plan and its metadata
+			let deposit = T::PlanDeposit::get();
+			T::Currency::reserve(&from, deposit)?;
+
+			let plan = Plan {
+				frequency,
+				amount,
+				remaining_payments: number_of_installment,
+				beneficiary: from,
+				trial,
+				deposit,
+			};
+
+			<Plans<T>>::insert(plan_id, plan.clone());
+
+			Self::deposit_event(Event::PlanCreated(plan_id, plan));
+
+			if !metadata.is_empty() {
+				<PlanMetadata<T>>::insert(plan_id, metadata.clone());
+				Self::deposit_event(Event::PlanMetadataSet(plan_id, metadata));
+			}
+
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::set_plan_metadata(metadata.len() as u32))]
+		pub fn set_plan_metadata(
+			origin: OriginFor<T>,
+			plan_id: PlanId,
+			metadata: Vec<u8>,
+		) -> DispatchResult {
+			let from = ensure_signed(origin)?;
+
+			let plan = Self::subscription_plans(plan_id).ok_or(Error::<T>::UnknownPlan)?;
+			ensure!(plan.beneficiary == from, Error::<T>::CallerIsNotPlanOwner);
+
+			let metadata: BoundedVec<u8, T::MaxMetadataLength> =
+				metadata.try_into().map_err(|_| Error::<T>::MetadataTooLong)?;
+
+			// An empty blob clears the metadata rather than storing nothing
+			if metadata.is_empty() {
+				<PlanMetadata<T>>::remove(plan_id);
+			} else {
+				<PlanMetadata<T>>::insert(plan_id, metadata.clone());
+			}
+
+			Self::deposit_event(Event::PlanMetadataSet(plan_id, metadata));
+			Ok(())
+		}
+
+		/// Change the price of a plan. New subscribers always pay the new price. With
+		/// `PriceUpdateMode::Grandfather` existing subscribers keep their price, with
+		/// `PriceUpdateMode::Migrate` they pay the new one from `PriceChangeNotice` blocks from
+		/// now unless they unsubscribe or agree on an amendment in the meantime. Either mode
+		/// replaces a migration still pending on the plan.
+		#[pallet::weight(T::WeightInfo::update_plan_price())]
+		pub fn update_plan_price(
+			origin: OriginFor<T>,
+			plan_id: PlanId,
+			amount: BalanceOf<T>,
+			mode: PriceUpdateMode,
+		) -> DispatchResult {
+			let from = ensure_signed(origin)?;
+
+			let plan = Self::subscription_plans(plan_id).ok_or(Error::<T>::UnknownPlan)?;
+			ensure!(plan.beneficiary == from, Error::<T>::CallerIsNotPlanOwner);
+			ensure!(!amount.is_zero(), Error::<T>::InvalidSubscription);
+
+			let effective_at = match mode {
+				PriceUpdateMode::Grandfather => {
+					<PlanPriceMigrations<T>>::remove(plan_id);
+					None
+				},
+				PriceUpdateMode::Migrate => {
+					let effective_at = <frame_system::Pallet<T>>::block_number()
+						.saturating_add(T::PriceChangeNotice::get());
+					// Only the subscriptions existing by now are concerned, later ones already
+					// pay the new price
+					let pending = Self::plan_subscription_count(plan_id);
+					if pending == 0 {
+						<PlanPriceMigrations<T>>::remove(plan_id);
+					} else {
+						let id = Self::price_migration_nonce();
+						let next_nonce = id.checked_add(1).ok_or(Error::<T>::NonceOverflow)?;
+						<PriceMigrationNonce<T>>::put(next_nonce);
+						<PlanPriceMigrations<T>>::insert(
+							plan_id,
+							PriceMigration {
+								id,
+								amount,
+								effective_at,
+								subscribed_before: Self::subscription_nonce(),
+								pending,
+							},
+						);
+					}
+					Some(effective_at)
+				},
+			};
+
+			<Plans<T>>::insert(
+				plan_id,
+				Plan {
+					amount,
+					..plan.clone()
+				},
+			);
+
+			Self::deposit_event(Event::PlanPriceUpdated {
+				plan_id,
+				old_amount: plan.amount,
+				new_amount: amount,
+				mode,
+				effective_at,
+			});
+
+			Ok(())
+		}
+
+		#[pallet::weight(
+			T::WeightInfo::subscribe_to_plan()
+				.saturating_add(Pallet::<T>::schedule_lookahead_weight())
+		)]
+		pub fn subscribe_to_plan(origin: OriginFor<T>, plan_id: PlanId) -> DispatchResult {
+			let from = ensure_signed(origin)?;
+
+			let plan = Self::subscription_plans(plan_id).ok_or(Error::<T>::UnknownPlan)?;
+
+			// A payer only gets the trial the first time they subscribe to the plan
+			let with_trial =
+				!plan.trial.is_zero() && !<TrialsUsed<T>>::contains_key(plan_id, &from);
+			let mut start_at = <frame_system::Pallet<T>>::block_number() + 1u32.into();
+			if with_trial {
+				start_at += plan.trial;
+			}
+
+			let subscription_id = Self::subscription_nonce();
+			Self::new_subscription(
+				from.clone(),
+				SubscriptionTerms {
+					beneficiary: plan.beneficiary,
+					amount: plan.amount,
+					frequency: plan.frequency,
+					remaining_payments: plan.remaining_payments,
+					asset: None,
+					existence_policy: ExistencePolicy::default(),
+					start_at,
+					plan_id: Some(plan_id),
+					prepaid: false,
+				},
+			)?;
+
+			if with_trial {
+				<TrialsUsed<T>>::insert(plan_id, &from, ());
+				Self::deposit_event(Event::TrialStarted {
+					plan_id,
+					subscription_id,
+					payer: from,
+					first_charge_at: start_at,
+				});
+			}
+
+			Ok(())
+		}
+
+		/// Stop the instalments of a subscription until the payer resumes it. Only the payer can
+		/// pause, and not while a failed instalment is being retried, so that pausing cannot be
+		/// used to dodge the cancellation after `MaxRetries` failures.
+		#[pallet::weight(T::WeightInfo::pause())]
+		pub fn pause(origin: OriginFor<T>, subscription_id: SubscriptionId) -> DispatchResult {
+			let from = ensure_signed(origin)?;
+
+			let subscription_data = Self::subscriptions_by_id(subscription_id)
+				.ok_or(Error::<T>::UnknownSubscription)?;
+
+			ensure!(
+				subscription_data.payer == from,
+				Error::<T>::CallerIsNotSubscriber
+			);
+			Self::ensure_ongoing(subscription_data.status)?;
+			ensure!(
+				subscription_data.status != SubscriptionStatus::Paused,
+				Error::<T>::SubscriptionIsPaused,
+			);
+			ensure!(
+				subscription_data.status != SubscriptionStatus::Delinquent,
+				Error::<T>::SubscriptionIsDelinquent,
+			);
+
+			// Only the record by id is kept, nothing gets executed until the subscription is
+			// resumed
+			Self::unschedule_subscription(subscription_data.scheduled_at, subscription_id);
+			<SubscriptionsById<T>>::insert(
+				subscription_id,
+				InstalmentData {
+					status: SubscriptionStatus::Paused,
+					..subscription_data
+				},
+			);
+
+			Self::deposit_event(Event::SubscriptionPaused { subscription_id });
+
+			Ok(())
+		}
+
+		#[pallet::weight(
+			T::WeightInfo::resume()
+				.saturating_add(Pallet::<T>::schedule_lookahead_weight())
+		)]
+		pub fn resume(origin: OriginFor<T>, subscription_id: SubscriptionId) -> DispatchResult {
+			let from = ensure_signed(origin)?;
+
+			let subscription_data = Self::subscriptions_by_id(subscription_id)
+				.ok_or(Error::<T>::UnknownSubscription)?;
+
+			ensure!(
+				subscription_data.payer == from,
+				Error::<T>::CallerIsNotSubscriber
+			);
+			ensure!(
+				subscription_data.status == SubscriptionStatus::Paused,
+				Error::<T>::SubscriptionIsNotPaused,
+			);
 
-			Self::deposit_event(Event::Unsubscription(subscription_data));
+			let next_due = <frame_system::Pallet<T>>::block_number() + 1u32.into();
+			let scheduled_at = Self::schedule_subscription(next_due, subscription_id)?;
+			<SubscriptionsById<T>>::insert(
+				subscription_id,
+				InstalmentData {
+					scheduled_at,
+					next_due,
+					status: SubscriptionStatus::Active,
+					..subscription_data
+				},
+			);
+
+			Self::deposit_event(Event::SubscriptionResumed {
+				subscription_id,
+				scheduled_at,
+			});
+
+			Ok(())
+		}
+
+		/// Give back `amount` of what was paid for the subscription to the payer. Only the
+		/// beneficiary can refund, and never more than was paid in total. Subscriptions that are
+		/// over can be refunded until they are reaped.
+		#[pallet::weight(T::WeightInfo::refund())]
+		pub fn refund(
+			origin: OriginFor<T>,
+			subscription_id: SubscriptionId,
+			amount: BalanceOf<T>,
+		) -> DispatchResult {
+			let from = ensure_signed(origin)?;
+
+			let subscription_data = Self::subscriptions_by_id(subscription_id)
+				.ok_or(Error::<T>::UnknownSubscription)?;
+
+			ensure!(
+				subscription_data.beneficiary == from,
+				Error::<T>::CallerIsNotBeneficiary
+			);
+			ensure!(!amount.is_zero(), Error::<T>::RefundIsZero);
+			let refunded = subscription_data.refunded.saturating_add(amount);
+			ensure!(
+				refunded <= subscription_data.total_paid,
+				Error::<T>::RefundExceedsPayments
+			);
+
+			Self::transfer(
+				subscription_data.asset,
+				&from,
+				&subscription_data.payer,
+				amount,
+				ExistenceRequirement::KeepAlive,
+			)?;
+			<SubscriptionsById<T>>::insert(
+				subscription_id,
+				InstalmentData {
+					refunded,
+					..subscription_data
+				},
+			);
+
+			Self::deposit_event(Event::Refunded {
+				subscription_id,
+				amount,
+			});
+
+			Ok(())
+		}
+
+		/// Propose new terms for the subscription, either as the payer or the beneficiary. They
+		/// replace any previous proposal and apply from the next instalment once the other party
+		/// accepts them.
+		#[pallet::weight(T::WeightInfo::propose_amendment())]
+		pub fn propose_amendment(
+			origin: OriginFor<T>,
+			subscription_id: SubscriptionId,
+			amount: BalanceOf<T>,
+			frequency: T::BlockNumber,
+			number_of_installment: Option<u32>,
+		) -> DispatchResult {
+			let from = ensure_signed(origin)?;
+
+			let subscription_data = Self::subscriptions_by_id(subscription_id)
+				.ok_or(Error::<T>::UnknownSubscription)?;
+
+			ensure!(
+				subscription_data.payer == from || subscription_data.beneficiary == from,
+				Error::<T>::CallerIsNotParty
+			);
+			Self::ensure_ongoing(subscription_data.status)?;
+			// Prepaid subscriptions have to know how much to keep in reserve
+			ensure!(
+				Self::are_valid_terms(amount, frequency, number_of_installment)
+					&& !(subscription_data.prepaid && number_of_installment.is_none()),
+				Error::<T>::InvalidSubscription
+			);
+
+			let amendment = Amendment {
+				proposer: from,
+				amount,
+				frequency,
+				remaining_payments: number_of_installment,
+			};
+			<Amendments<T>>::insert(subscription_id, amendment.clone());
+
+			Self::deposit_event(Event::AmendmentProposed {
+				subscription_id,
+				amendment,
+			});
+
+			Ok(())
+		}
+
+		/// Accept the terms the other party of a subscription proposed. The terms are given again
+		/// so that a proposal replaced in the meantime is not accepted by mistake.
+		#[pallet::weight(T::WeightInfo::accept_amendment())]
+		pub fn accept_amendment(
+			origin: OriginFor<T>,
+			subscription_id: SubscriptionId,
+			amount: BalanceOf<T>,
+			frequency: T::BlockNumber,
+			number_of_installment: Option<u32>,
+		) -> DispatchResult {
+			let from = ensure_signed(origin)?;
+
+			let subscription_data = Self::subscriptions_by_id(subscription_id)
+				.ok_or(Error::<T>::UnknownSubscription)?;
+			let amendment =
+				Self::amendments(subscription_id).ok_or(Error::<T>::NoAmendmentProposed)?;
+
+			ensure!(
+				subscription_data.payer == from || subscription_data.beneficiary == from,
+				Error::<T>::CallerIsNotParty
+			);
+			ensure!(amendment.proposer != from, Error::<T>::CallerIsProposer);
+			ensure!(
+				amendment.amount == amount
+					&& amendment.frequency == frequency
+					&& amendment.remaining_payments == number_of_installment,
+				Error::<T>::AmendmentMismatch
+			);
+
+			// The prepaid reserve follows the new terms
+			if subscription_data.prepaid {
+				let reserved = Self::prepaid_reserve(
+					subscription_data.amount,
+					subscription_data.remaining_payments,
+				);
+				let required =
+					Self::prepaid_reserve(amendment.amount, amendment.remaining_payments);
+				if required > reserved {
+					T::Currency::reserve(&subscription_data.payer, required - reserved)?;
+				} else {
+					T::Currency::unreserve(&subscription_data.payer, reserved - required);
+				}
+			}
+
+			// Terms agreed on after a price migration of the plan was decided are not overridden
+			// by it
+			let price_migration =
+				match Self::pending_price_migration(subscription_id, &subscription_data) {
+					Some((plan_id, migration)) => {
+						let id = migration.id;
+						Self::settle_price_migration(plan_id, migration);
+						Some(id)
+					},
+					None => subscription_data.price_migration,
+				};
+
+			<Amendments<T>>::remove(subscription_id);
+			<SubscriptionsById<T>>::insert(
+				subscription_id,
+				InstalmentData {
+					amount: amendment.amount,
+					frequency: amendment.frequency,
+					remaining_payments: amendment.remaining_payments,
+					price_migration,
+					..subscription_data
+				},
+			);
+
+			Self::deposit_event(Event::AmendmentAccepted {
+				subscription_id,
+				amendment,
+			});
+
+			Ok(())
+		}
+
+		/// Remove a subscription that completed, was terminated or was cancelled from the records
+		/// and give its deposit back to the payer. Either party can reap it once
+		/// `ArchivePeriod` blocks have passed.
+		#[pallet::weight(T::WeightInfo::reap())]
+		pub fn reap(origin: OriginFor<T>, subscription_id: SubscriptionId) -> DispatchResult {
+			let from = ensure_signed(origin)?;
+
+			let subscription_data = Self::subscriptions_by_id(subscription_id)
+				.ok_or(Error::<T>::UnknownSubscription)?;
+
+			ensure!(
+				subscription_data.payer == from || subscription_data.beneficiary == from,
+				Error::<T>::CallerIsNotParty
+			);
+			ensure!(
+				subscription_data.status.is_over(),
+				Error::<T>::SubscriptionIsNotOver
+			);
+			let now = <frame_system::Pallet<T>>::block_number();
+			ensure!(
+				now >= subscription_data.scheduled_at.saturating_add(T::ArchivePeriod::get()),
+				Error::<T>::ArchivePeriodNotOver
+			);
+
+			T::Currency::unreserve(&subscription_data.payer, subscription_data.deposit);
+			<SubscriptionsById<T>>::remove(subscription_id);
+
+			Self::deposit_event(Event::SubscriptionReaped { subscription_id });
+
+			Ok(())
+		}
+
+		/// Remove a plan along with its metadata and give its deposit back to the owner. Nobody
+		/// can subscribe to it anymore, existing subscribers keep their terms and a pending price
+		/// migration is dropped. Up to `MaxTrialsRemoved` records of the payers who had the trial
+		/// are removed too, the ones past it stay in storage for good.
+		#[pallet::weight(T::WeightInfo::remove_plan(T::MaxTrialsRemoved::get()))]
+		pub fn remove_plan(origin: OriginFor<T>, plan_id: PlanId) -> DispatchResult {
+			let from = ensure_signed(origin)?;
+
+			let plan = Self::subscription_plans(plan_id).ok_or(Error::<T>::UnknownPlan)?;
+			ensure!(plan.beneficiary == from, Error::<T>::CallerIsNotPlanOwner);
+
+			T::Currency::unreserve(&plan.beneficiary, plan.deposit);
+			<Plans<T>>::remove(plan_id);
+			<PlanMetadata<T>>::remove(plan_id);
+			<PlanPriceMigrations<T>>::remove(plan_id);
+			<PlanSubscriptionCount<T>>::remove(plan_id);
+			<TrialsUsed<T>>::remove_prefix(plan_id, Some(T::MaxTrialsRemoved::get()));
+
+			Self::deposit_event(Event::PlanRemoved { plan_id });
 
 			Ok(())
 		}
 	}
 
 	impl<T: Config> Pallet<T> {
-		fn schedule_subscriptions(
+		/// The ids of the ongoing subscriptions paid by `payer`, in the order they were created
+		pub fn subscriptions_by_payer(
+			payer: impl codec::EncodeLike<T::AccountId>,
+		) -> Vec<SubscriptionId> {
+			let mut subscriptions: Vec<SubscriptionId> =
+				<SubscriptionsByPayer<T>>::iter_key_prefix(payer).collect();
+			subscriptions.sort_unstable();
+			subscriptions
+		}
+
+		/// The ids of the ongoing subscriptions paying `beneficiary`, in the order they were
+		/// created
+		pub fn subscriptions_by_beneficiary(
+			beneficiary: impl codec::EncodeLike<T::AccountId>,
+		) -> Vec<SubscriptionId> {
+			let mut subscriptions: Vec<SubscriptionId> =
+				<SubscriptionsByBeneficiary<T>>::iter_key_prefix(beneficiary).collect();
+			subscriptions.sort_unstable();
+			subscriptions
+		}
+
+		fn are_valid_terms(
+			amount: BalanceOf<T>,
+			frequency: T::BlockNumber,
+			number_of_installment: Option<u32>,
+		) -> bool {
+			!frequency.is_zero()
+				&& !amount.is_zero()
+				&& match number_of_installment {
+					Some(x) => x >= 1,
+					None => true,
+				}
+		}
+
+		fn new_subscription(
+			from: T::AccountId,
+			terms: SubscriptionTerms<T::BlockNumber, BalanceOf<T>, T::AccountId, AssetIdOf<T>>,
+		) -> DispatchResult {
+			ensure!(
+				Self::are_valid_terms(terms.amount, terms.frequency, terms.remaining_payments)
+					&& terms.beneficiary != from,
+				Error::<T>::InvalidSubscription
+			);
+			// Prepaid subscriptions have to know how much to keep in reserve, and only the native
+			// currency can be reserved
+			if terms.prepaid {
+				ensure!(
+					terms.remaining_payments.is_some(),
+					Error::<T>::InvalidSubscription
+				);
+				ensure!(terms.asset.is_none(), Error::<T>::PrepaidInAsset);
+			}
+
+			let subscription_id = Self::subscription_nonce();
+			let next_nonce = subscription_id.checked_add(1).ok_or(Error::<T>::NonceOverflow)?;
+
+			// The deposit pays for the storage used by the subscription, prepaid instalments are
+			// set aside as well so the beneficiary is guaranteed to be paid
+			let deposit = T::SubscriptionDeposit::get();
+			let mut reserve = deposit;
+			if terms.prepaid {
+				reserve = reserve.saturating_add(Self::prepaid_reserve(
+					terms.amount,
+					terms.remaining_payments,
+				));
+			}
+			T::Currency::reserve(&from, reserve)?;
+
+			let scheduled_at = Self::schedule_subscription(terms.start_at, subscription_id)?;
+
+			let subscription = InstalmentData {
+				frequency: terms.frequency,
+				amount: terms.amount,
+				remaining_payments: terms.remaining_payments,
+				beneficiary: terms.beneficiary,
+				payer: from,
+				scheduled_at,
+				status: SubscriptionStatus::Active,
+				failed_attempts: 0,
+				next_due: terms.start_at,
+				prepaid: terms.prepaid,
+				deposit,
+				asset: terms.asset,
+				existence_policy: terms.existence_policy,
+				total_paid: Zero::zero(),
+				refunded: Zero::zero(),
+				plan_id: terms.plan_id,
+				price_migration: None,
+			};
+
+			<SubscriptionNonce<T>>::put(next_nonce);
+			if let Some(plan_id) = subscription.plan_id {
+				<PlanSubscriptionCount<T>>::mutate(plan_id, |count| {
+					*count = count.saturating_add(1)
+				});
+			}
+			<SubscriptionsByPayer<T>>::insert(&subscription.payer, subscription_id, ());
+			<SubscriptionsByBeneficiary<T>>::insert(&subscription.beneficiary, subscription_id, ());
+			<SubscriptionsById<T>>::insert(subscription_id, subscription.clone());
+
+			Self::deposit_event(Event::Subscription(subscription_id, subscription));
+			Ok(())
+		}
+
+		/// Execute the instalments scheduled from `since` up to `now` in order, as long as the
+		/// weight stays under `limit`. Whatever is left is picked up again from `IncompleteSince`.
+		fn execute_scheduled(now: T::BlockNumber, since: T::BlockNumber, limit: Weight) -> Weight {
+			let mut total_weight: Weight = T::WeightInfo::on_initialize(0);
+			let instalment_weight = T::WeightInfo::on_initialize(1)
+				.saturating_sub(T::WeightInfo::on_initialize(0))
+				.saturating_add(Self::schedule_lookahead_weight());
+
+			let mut when = since;
+			total_weight += T::DbWeight::get().reads_writes(1 as Weight, 1 as Weight);
+
+			loop {
+				let mut scheduled_subscriptions = <Subscriptions<T>>::take(when).into_iter();
+
+				while total_weight < limit {
+					let subscription_id = match scheduled_subscriptions.next() {
+						Some(id) => id,
+						None => break,
+					};
+
+					// Benchmarked against the most expensive path, where the instalment is
+					// rescheduled
+					total_weight += instalment_weight;
+					let _ = Self::execute_instalment(now, subscription_id);
+				}
+
+				let remaining: Vec<SubscriptionId> = scheduled_subscriptions.collect();
+				if !remaining.is_empty() {
+					// Never fails, there are less instalments left than were taken out
+					if let Ok(remaining) = BoundedVec::try_from(remaining) {
+						<Subscriptions<T>>::insert(when, remaining);
+					}
+					<IncompleteSince<T>>::put(when);
+					break
+				}
+
+				if when >= now {
+					break
+				}
+				when += 1u32.into();
+				if total_weight >= limit {
+					<IncompleteSince<T>>::put(when);
+					break
+				}
+				total_weight += T::DbWeight::get().reads_writes(1 as Weight, 1 as Weight);
+			}
+
+			total_weight
+		}
+
+		/// Pay the instalment of the subscription and schedule the next one, return the amount
+		/// paid if the payment went through
+		fn execute_instalment(
+			now: T::BlockNumber,
+			subscription_id: SubscriptionId,
+		) -> Option<BalanceOf<T>> {
+			let sub_info = <SubscriptionsById<T>>::get(subscription_id)?;
+
+			let sub_info = Self::migrate_price(now, subscription_id, sub_info);
+
+			if now > sub_info.scheduled_at {
+				Self::deposit_event(Event::PaymentDelayed {
+					subscription_id,
+					delay: now - sub_info.scheduled_at,
+				});
+			}
+
+			let res_transfer = Self::pay_instalment(&sub_info);
+
+			// Cases where we don't want to execute another instalment of this subscription
+			if let Err(error) = res_transfer {
+				Self::deposit_event(Event::PaymentFailed {
+					subscription_id,
+					error,
+				});
+
+				let attempt = sub_info.failed_attempts.saturating_add(1);
+				if attempt > T::MaxRetries::get() {
+					Self::archive_subscription(
+						subscription_id,
+						&sub_info,
+						SubscriptionStatus::Cancelled,
+					);
+					Self::deposit_event(Event::SubscriptionCancelled { subscription_id });
+					return None
+				}
+
+				// Keep the subscription on record and give the payer some time to top up
+				let retry_at = Self::reschedule_subscription(
+					now + T::RetryInterval::get().max(1u32.into()),
+					subscription_id,
+				);
+				<SubscriptionsById<T>>::insert(
+					subscription_id,
+					InstalmentData {
+						status: SubscriptionStatus::Delinquent,
+						failed_attempts: attempt,
+						scheduled_at: retry_at,
+						..sub_info
+					},
+				);
+				Self::deposit_event(Event::PaymentRetryScheduled {
+					subscription_id,
+					attempt,
+					retry_at,
+				});
+				return None
+			}
+			if sub_info.status == SubscriptionStatus::Delinquent {
+				Self::deposit_event(Event::PaymentRecovered { subscription_id });
+			}
+
+			// Instalments that are late are caught up with as soon as possible
+			let next_anchor = sub_info.next_due + sub_info.frequency;
+			let remaining_payments = sub_info.remaining_payments.map(|x| x - 1);
+			let paid = sub_info.amount;
+			let total_paid = sub_info.total_paid.saturating_add(paid);
+			let scheduled_at = if remaining_payments == Some(0) {
+				None
+			} else {
+				Some(Self::reschedule_subscription(
+					next_anchor.max(now + 1u32.into()),
+					subscription_id,
+				))
+			};
+			Self::deposit_event(Event::PaymentExecuted {
+				subscription_id,
+				payer: sub_info.payer.clone(),
+				beneficiary: sub_info.beneficiary.clone(),
+				amount: sub_info.amount,
+				remaining_payments,
+				scheduled_at,
+			});
+
+			let next_payment = match scheduled_at {
+				Some(next_payment) => next_payment,
+				None => {
+					Self::remove_from_indexes(subscription_id, &sub_info);
+					<Amendments<T>>::remove(subscription_id);
+					Self::deposit_event(Event::SubscriptionCompleted {
+						subscription_id,
+						payer: sub_info.payer.clone(),
+						beneficiary: sub_info.beneficiary.clone(),
+					});
+
+					// Every prepaid instalment has been taken out of the reserve, the deposit stays
+					// reserved for as long as the record is kept and is given back by `reap`
+					<SubscriptionsById<T>>::insert(
+						subscription_id,
+						InstalmentData {
+							remaining_payments,
+							total_paid,
+							scheduled_at: now,
+							status: SubscriptionStatus::Completed,
+							failed_attempts: 0,
+							..sub_info
+						},
+					);
+					return Some(paid)
+				},
+			};
+
+			<SubscriptionsById<T>>::insert(
+				subscription_id,
+				InstalmentData {
+					remaining_payments,
+					scheduled_at: next_payment,
+					status: SubscriptionStatus::Active,
+					failed_attempts: 0,
+					next_due: next_anchor,
+					total_paid,
+					..sub_info
+				},
+			);
+			Some(paid)
+		}
+
+		/// Move the subscription to the new price of its plan once the notice is over
+		fn migrate_price(
+			now: T::BlockNumber,
+			subscription_id: SubscriptionId,
+			subscription: InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId, AssetIdOf<T>>,
+		) -> InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId, AssetIdOf<T>> {
+			let (plan_id, migration) =
+				match Self::pending_price_migration(subscription_id, &subscription) {
+					Some(pending) => pending,
+					None => return subscription,
+				};
+			if now < migration.effective_at {
+				return subscription
+			}
+
+			let id = migration.id;
+			let amount = migration.amount;
+			Self::settle_price_migration(plan_id, migration);
+
+			Self::deposit_event(Event::SubscriptionPriceMigrated {
+				subscription_id,
+				plan_id,
+				amount,
+			});
+			InstalmentData {
+				amount,
+				price_migration: Some(id),
+				..subscription
+			}
+		}
+
+		/// The price migration of its plan the subscription still has to go through, if any
+		fn pending_price_migration(
+			subscription_id: SubscriptionId,
+			subscription: &InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId, AssetIdOf<T>>,
+		) -> Option<(PlanId, PriceMigration<T::BlockNumber, BalanceOf<T>>)> {
+			let plan_id = subscription.plan_id?;
+			let migration = Self::plan_price_migrations(plan_id)?;
+			if subscription_id >= migration.subscribed_before
+				|| subscription.price_migration == Some(migration.id)
+			{
+				return None
+			}
+			Some((plan_id, migration))
+		}
+
+		/// Count one subscription out of the price migration, it is removed with the last one
+		fn settle_price_migration(
+			plan_id: PlanId,
+			mut migration: PriceMigration<T::BlockNumber, BalanceOf<T>>,
+		) {
+			migration.pending = migration.pending.saturating_sub(1);
+			if migration.pending == 0 {
+				<PlanPriceMigrations<T>>::remove(plan_id);
+			} else {
+				<PlanPriceMigrations<T>>::insert(plan_id, migration);
+			}
+		}
+
+		/// Schedule the subscription at `when`, or at the first following block with some room
+		/// left within `MaxScheduleLookahead` blocks, and return the block it ended up in.
+		pub(crate) fn schedule_subscription(
+			when: T::BlockNumber,
+			subscription_id: SubscriptionId,
+		) -> Result<T::BlockNumber, DispatchError> {
+			let mut block = when;
+			for _ in 0..=T::MaxScheduleLookahead::get() {
+				if <Subscriptions<T>>::try_mutate(block, |current_subscriptions| {
+					current_subscriptions.try_push(subscription_id)
+				})
+				.is_ok()
+				{
+					return Ok(block)
+				}
+				block += 1u32.into();
+			}
+			Err(Error::<T>::ScheduleFull.into())
+		}
+
+		/// Schedule the next instalment of a subscription from the hooks, where there is no
+		/// caller to give the error to. When every block is full the instalment is left due at
+		/// `when`, where `execute_overdue` can still pick it up.
+		pub(crate) fn reschedule_subscription(
 			when: T::BlockNumber,
-			new_subscription: &[InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId>],
+			subscription_id: SubscriptionId,
+		) -> T::BlockNumber {
+			Self::schedule_subscription(when, subscription_id).unwrap_or_else(|_| {
+				Self::deposit_event(Event::InstalmentNotScheduled {
+					subscription_id,
+					due: when,
+				});
+				when
+			})
+		}
+
+		/// The weight of going through every full block `schedule_subscription` may look at, on
+		/// top of the first one counted in the weight of the calls
+		pub(crate) fn schedule_lookahead_weight() -> Weight {
+			T::WeightInfo::schedule_subscription(T::MaxScheduleLookahead::get())
+				.saturating_sub(T::WeightInfo::schedule_subscription(0))
+		}
+
+		/// Pay the instalment from the prepaid reserve, or from the free balance of the payer
+		/// for whatever the reserve does not cover
+		fn pay_instalment(
+			subscription: &InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId, AssetIdOf<T>>,
+		) -> DispatchResult {
+			// Hooks are not transactional, what was taken out of the reserve is put back when the
+			// rest of the instalment cannot be paid
+			with_transaction(|| {
+				let res = Self::try_pay_instalment(subscription);
+				if res.is_ok() {
+					TransactionOutcome::Commit(res)
+				} else {
+					TransactionOutcome::Rollback(res)
+				}
+			})
+		}
+
+		fn try_pay_instalment(
+			subscription: &InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId, AssetIdOf<T>>,
+		) -> DispatchResult {
+			let mut unpaid = subscription.amount;
+			if subscription.prepaid {
+				unpaid = T::Currency::repatriate_reserved(
+					&subscription.payer,
+					&subscription.beneficiary,
+					subscription.amount,
+					BalanceStatus::Free,
+				)?;
+			}
+			if unpaid.is_zero() {
+				return Ok(())
+			}
+
+			Self::transfer(
+				subscription.asset,
+				&subscription.payer,
+				&subscription.beneficiary,
+				unpaid,
+				subscription.existence_policy.into(),
+			)
+		}
+
+		/// Transfer `amount` of `asset`, or of the native currency when there is none
+		fn transfer(
+			asset: Option<AssetIdOf<T>>,
+			source: &T::AccountId,
+			dest: &T::AccountId,
+			amount: BalanceOf<T>,
+			existence_requirement: ExistenceRequirement,
+		) -> DispatchResult {
+			match asset {
+				Some(asset) => T::Assets::transfer(
+					asset,
+					source,
+					dest,
+					amount,
+					existence_requirement == ExistenceRequirement::KeepAlive,
+				)
+				.map(|_| ()),
+				None => T::Currency::transfer(source, dest, amount, existence_requirement),
+			}
+		}
+
+		fn prepaid_reserve(amount: BalanceOf<T>, remaining_payments: Option<u32>) -> BalanceOf<T> {
+			amount.saturating_mul(remaining_payments.unwrap_or_default().into())
+		}
+
+		/// End an unscheduled subscription, cancelled by the payer or terminated by the
+		/// beneficiary
+		fn end_subscription(
+			subscription_id: SubscriptionId,
+			subscription: InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId, AssetIdOf<T>>,
+			by_beneficiary: bool,
+		) {
+			if !by_beneficiary {
+				Self::archive_subscription(
+					subscription_id,
+					&subscription,
+					SubscriptionStatus::Cancelled,
+				);
+				Self::deposit_event(Event::Unsubscription(subscription_id, subscription));
+				return
+			}
+
+			Self::archive_subscription(
+				subscription_id,
+				&subscription,
+				SubscriptionStatus::Terminated,
+			);
+			Self::deposit_event(Event::SubscriptionTerminated {
+				subscription_id,
+				payer: subscription.payer,
+				beneficiary: subscription.beneficiary,
+			});
+		}
+
+		/// Keep the record of a subscription that ended early, along with the deposit, so that it
+		/// can still be refunded until reaped. What is left of the prepaid reserve goes back to the
+		/// payer.
+		fn archive_subscription(
+			subscription_id: SubscriptionId,
+			subscription: &InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId, AssetIdOf<T>>,
+			status: SubscriptionStatus,
 		) {
+			if subscription.prepaid {
+				T::Currency::unreserve(
+					&subscription.payer,
+					Self::prepaid_reserve(subscription.amount, subscription.remaining_payments),
+				);
+			}
+			Self::remove_from_indexes(subscription_id, subscription);
+			<Amendments<T>>::remove(subscription_id);
+			<SubscriptionsById<T>>::insert(
+				subscription_id,
+				InstalmentData {
+					scheduled_at: <frame_system::Pallet<T>>::block_number(),
+					status,
+					..subscription.clone()
+				},
+			);
+		}
+
+		fn ensure_ongoing(status: SubscriptionStatus) -> DispatchResult {
+			match status {
+				SubscriptionStatus::Completed => Err(Error::<T>::SubscriptionIsCompleted.into()),
+				SubscriptionStatus::Terminated => Err(Error::<T>::SubscriptionIsTerminated.into()),
+				SubscriptionStatus::Cancelled => Err(Error::<T>::SubscriptionIsCancelled.into()),
+				_ => Ok(()),
+			}
+		}
+
+		/// Called once per subscription when it ends, it no longer counts towards its plan either
+		fn remove_from_indexes(
+			subscription_id: SubscriptionId,
+			subscription: &InstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId, AssetIdOf<T>>,
+		) {
+			<SubscriptionsByPayer<T>>::remove(&subscription.payer, subscription_id);
+			<SubscriptionsByBeneficiary<T>>::remove(&subscription.beneficiary, subscription_id);
+
+			if let Some(plan_id) = subscription.plan_id {
+				// No zero count is left behind, the plan may be gone already
+				<PlanSubscriptionCount<T>>::mutate_exists(plan_id, |count| {
+					*count = count.and_then(|count| count.checked_sub(1)).filter(|count| *count > 0)
+				});
+				if let Some((plan_id, migration)) =
+					Self::pending_price_migration(subscription_id, subscription)
+				{
+					Self::settle_price_migration(plan_id, migration);
+				}
+			}
+		}
+
+		fn unschedule_subscription(when: T::BlockNumber, subscription_id: SubscriptionId) {
 			<Subscriptions<T>>::mutate(when, |current_subscriptions| {
-				current_subscriptions.extend_from_slice(new_subscription);
+				if let Some(index) =
+					current_subscriptions.iter().position(|id| *id == subscription_id)
+				{
+					// Keep the order of the instalments left, they are executed first come first
+					// served
+					current_subscriptions.remove(index);
+				}
 			});
 		}
 	}
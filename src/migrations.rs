@@ -0,0 +1,116 @@
+//! Storage migrations of the pallet, run from `on_runtime_upgrade`
+
+use crate::*;
+use frame_support::{
+	pallet_prelude::*,
+	sp_runtime::traits::Zero,
+	sp_std::vec::Vec,
+	storage::migration::storage_key_iter,
+	traits::{PalletInfoAccess, StorageVersion},
+};
+
+/// From the first layout (version 0), where `Subscriptions` held the data of every instalment due
+/// at a block and subscriptions had no id
+pub mod v1 {
+	use super::*;
+
+	/// `InstalmentData` as stored by the first layout, in `Subscriptions` and `Plans`
+	#[derive(Encode, Decode)]
+	pub struct OldInstalmentData<BlockNumber, Balance, AccountId> {
+		pub frequency: BlockNumber,
+		pub amount: Balance,
+		pub remaining_payments: Option<u32>,
+		pub beneficiary: AccountId,
+		pub payer: AccountId,
+	}
+
+	/// Give every scheduled instalment an id and store its data in `SubscriptionsById`, then
+	/// schedule it by id again. The payers never reserved a deposit so none is kept, and the
+	/// instalments are paid in the native currency without taking the payer below the
+	/// existential deposit, as they were before. Plans keep their terms with no trial.
+	pub fn migrate<T: Config>() -> Weight {
+		let on_chain = StorageVersion::get::<Pallet<T>>();
+		if on_chain != 0 {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut reads: Weight = 2;
+		let mut writes: Weight = 2;
+
+		let pallet = <Pallet<T> as PalletInfoAccess>::name().as_bytes();
+		// Taken out first, the same map holds the ids afterwards
+		let scheduled: Vec<_> = storage_key_iter::<
+			T::BlockNumber,
+			Vec<OldInstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId>>,
+			Twox64Concat,
+		>(pallet, b"Subscriptions")
+		.drain()
+		.collect();
+
+		let now = <frame_system::Pallet<T>>::block_number();
+		let mut nonce = Pallet::<T>::subscription_nonce();
+		for (when, instalments) in scheduled {
+			reads = reads.saturating_add(1);
+			writes = writes.saturating_add(1);
+
+			for old in instalments {
+				let subscription_id = nonce;
+				nonce = nonce.saturating_add(1);
+
+				// Every block up to `MaxScheduleLookahead` may be looked at
+				let next_due = when.max(now);
+				let scheduled_at = Pallet::<T>::reschedule_subscription(next_due, subscription_id);
+				reads =
+					reads.saturating_add(T::MaxScheduleLookahead::get().saturating_add(1).into());
+				writes = writes.saturating_add(4);
+
+				let subscription = InstalmentData {
+					frequency: old.frequency,
+					amount: old.amount,
+					remaining_payments: old.remaining_payments,
+					beneficiary: old.beneficiary,
+					payer: old.payer,
+					scheduled_at,
+					status: SubscriptionStatus::Active,
+					failed_attempts: 0,
+					next_due,
+					prepaid: false,
+					deposit: Zero::zero(),
+					asset: None,
+					existence_policy: ExistencePolicy::KeepAlive,
+					total_paid: Zero::zero(),
+					refunded: Zero::zero(),
+					plan_id: None,
+					price_migration: None,
+				};
+				<SubscriptionsByPayer<T>>::insert(&subscription.payer, subscription_id, ());
+				<SubscriptionsByBeneficiary<T>>::insert(
+					&subscription.beneficiary,
+					subscription_id,
+					(),
+				);
+				<SubscriptionsById<T>>::insert(subscription_id, subscription);
+			}
+		}
+		<SubscriptionNonce<T>>::put(nonce);
+
+		<Plans<T>>::translate::<OldInstalmentData<T::BlockNumber, BalanceOf<T>, T::AccountId>, _>(
+			|_, old| {
+				reads = reads.saturating_add(1);
+				writes = writes.saturating_add(1);
+				Some(Plan {
+					frequency: old.frequency,
+					amount: old.amount,
+					remaining_payments: old.remaining_payments,
+					beneficiary: old.beneficiary,
+					trial: Zero::zero(),
+					deposit: Zero::zero(),
+				})
+			},
+		);
+
+		StorageVersion::new(1).put::<Pallet<T>>();
+
+		T::DbWeight::get().reads_writes(reads, writes)
+	}
+}
@@ -0,0 +1,233 @@
+use super::mock::*;
+use crate::{Amendment, Error, ExistencePolicy};
+use frame_support::{assert_noop, assert_ok};
+
+#[test]
+fn amendment_applies_from_next_instalment() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+		let frequency = 5;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			frequency,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+		run_to_block(2);
+
+		// BOB raises the price, ALICE agrees
+		assert_ok!(PalletSubscription::propose_amendment(
+			Origin::signed(BOB()),
+			0,
+			amount * 2,
+			frequency * 2,
+			Some(3)
+		));
+		let amendment = Amendment {
+			proposer: BOB(),
+			amount: amount * 2,
+			frequency: frequency * 2,
+			remaining_payments: Some(3),
+		};
+		assert_eq!(PalletSubscription::amendments(0), Some(amendment.clone()));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::PalletSubscription(crate::Event::AmendmentProposed {
+				subscription_id: 0,
+				amendment: amendment.clone(),
+			})
+		);
+
+		assert_ok!(PalletSubscription::accept_amendment(
+			Origin::signed(ALICE()),
+			0,
+			amount * 2,
+			frequency * 2,
+			Some(3)
+		));
+		assert_eq!(PalletSubscription::amendments(0), None);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::PalletSubscription(crate::Event::AmendmentAccepted {
+				subscription_id: 0,
+				amendment,
+			})
+		);
+
+		// The instalment already scheduled uses the new amount, the next one the new frequency
+		let bob_balance_before = Balances::free_balance(&BOB());
+		run_to_block(2 + frequency);
+		assert_eq!(
+			Balances::free_balance(&BOB()),
+			bob_balance_before + amount * 2
+		);
+
+		let subscription = PalletSubscription::subscriptions_by_id(0).unwrap();
+		assert_eq!(subscription.remaining_payments, Some(2));
+		assert_eq!(subscription.scheduled_at, 2 + frequency * 3);
+	})
+}
+
+#[test]
+fn amendment_needs_the_other_party() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			4000,
+			5,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		assert_noop!(
+			PalletSubscription::accept_amendment(Origin::signed(BOB()), 0, 4000, 5, None),
+			Error::<TestRuntime>::NoAmendmentProposed
+		);
+		assert_noop!(
+			PalletSubscription::propose_amendment(Origin::signed(CHARLIE()), 0, 8000, 5, None),
+			Error::<TestRuntime>::CallerIsNotParty
+		);
+		assert_noop!(
+			PalletSubscription::propose_amendment(Origin::signed(ALICE()), 0, 0, 5, None),
+			Error::<TestRuntime>::InvalidSubscription
+		);
+
+		assert_ok!(PalletSubscription::propose_amendment(
+			Origin::signed(ALICE()),
+			0,
+			3000,
+			5,
+			None
+		));
+		assert_noop!(
+			PalletSubscription::accept_amendment(Origin::signed(ALICE()), 0, 3000, 5, None),
+			Error::<TestRuntime>::CallerIsProposer
+		);
+		assert_noop!(
+			PalletSubscription::accept_amendment(Origin::signed(CHARLIE()), 0, 3000, 5, None),
+			Error::<TestRuntime>::CallerIsNotParty
+		);
+
+		// Dropped along with the subscription
+		assert_ok!(PalletSubscription::unsubscribe_by_id(
+			Origin::signed(ALICE()),
+			0
+		));
+		assert_eq!(PalletSubscription::amendments(0), None);
+	})
+}
+
+#[test]
+fn amendment_adjusts_prepaid_reserve() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			5,
+			Some(3),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			true
+		));
+		assert_noop!(
+			PalletSubscription::propose_amendment(Origin::signed(BOB()), 0, amount, 5, None),
+			Error::<TestRuntime>::InvalidSubscription
+		);
+
+		assert_ok!(PalletSubscription::propose_amendment(
+			Origin::signed(BOB()),
+			0,
+			amount * 2,
+			5,
+			Some(2)
+		));
+		assert_ok!(PalletSubscription::accept_amendment(
+			Origin::signed(ALICE()),
+			0,
+			amount * 2,
+			5,
+			Some(2)
+		));
+		assert_eq!(Balances::reserved_balance(&ALICE()), amount * 4);
+
+		assert_ok!(PalletSubscription::propose_amendment(
+			Origin::signed(ALICE()),
+			0,
+			amount,
+			5,
+			Some(1)
+		));
+		assert_ok!(PalletSubscription::accept_amendment(
+			Origin::signed(BOB()),
+			0,
+			amount,
+			5,
+			Some(1)
+		));
+		assert_eq!(Balances::reserved_balance(&ALICE()), amount);
+	})
+}
+
+#[test]
+fn amendment_replaced_before_accept() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			5,
+			Some(3),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			true
+		));
+		assert_ok!(PalletSubscription::propose_amendment(
+			Origin::signed(BOB()),
+			0,
+			amount + 500,
+			5,
+			Some(3)
+		));
+
+		// BOB swaps the proposal for a much more expensive one before ALICE's accept lands
+		assert_ok!(PalletSubscription::propose_amendment(
+			Origin::signed(BOB()),
+			0,
+			amount * 10,
+			5,
+			Some(30)
+		));
+		assert_noop!(
+			PalletSubscription::accept_amendment(
+				Origin::signed(ALICE()),
+				0,
+				amount + 500,
+				5,
+				Some(3)
+			),
+			Error::<TestRuntime>::AmendmentMismatch
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().amount,
+			amount
+		);
+		assert_eq!(Balances::reserved_balance(&ALICE()), amount * 3);
+	})
+}
@@ -0,0 +1,250 @@
+use super::mock::*;
+use crate::{Error, ExistencePolicy, PriceUpdateMode, WeightInfo};
+use frame_support::{assert_noop, assert_ok, traits::Get};
+use sp_runtime::Perbill;
+
+#[test]
+fn execute_overdue_pays_keeper() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		// Only two instalments fit in a block
+		MaximumWeight::set(&<() as WeightInfo>::on_initialize(2));
+		KeeperRewardShare::set(&Perbill::from_percent(10));
+		let amount = 4000;
+		let frequency = 5;
+
+		for _ in 0..3 {
+			assert_ok!(PalletSubscription::subscribe(
+				Origin::signed(ALICE()),
+				BOB(),
+				amount,
+				frequency,
+				None,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
+			));
+		}
+
+		run_to_block(2);
+		assert_eq!(PalletSubscription::subscriptions(2).to_vec(), vec![2]);
+
+		let bob_balance_before = Balances::free_balance(&BOB());
+		let charlie_balance_before = Balances::free_balance(&CHARLIE());
+		assert_ok!(PalletSubscription::execute_overdue(
+			Origin::signed(CHARLIE()),
+			2
+		));
+
+		assert_eq!(
+			Balances::free_balance(&BOB()),
+			bob_balance_before + amount - 400
+		);
+		assert_eq!(
+			Balances::free_balance(&CHARLIE()),
+			charlie_balance_before + 400
+		);
+		assert!(PalletSubscription::subscriptions(2).is_empty());
+		assert!(PalletSubscription::subscriptions(2 + frequency).contains(&2));
+
+		let expected_event = Event::PalletSubscription(crate::Event::KeeperRewarded {
+			subscription_id: 2,
+			keeper: CHARLIE(),
+			reward: 400,
+		});
+		assert_eq!(System::events().last().unwrap().event, expected_event);
+
+		// Not paid a second time by the hook
+		let bob_balance_before = Balances::free_balance(&BOB());
+		run_to_block(3);
+		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before);
+	})
+}
+
+#[test]
+fn execute_overdue_without_reward() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			5,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		// The hook did not run for block 2
+		System::set_block_number(3);
+
+		let bob_balance_before = Balances::free_balance(&BOB());
+		let charlie_balance_before = Balances::free_balance(&CHARLIE());
+		assert_ok!(PalletSubscription::execute_overdue(
+			Origin::signed(CHARLIE()),
+			0
+		));
+
+		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before + amount);
+		assert_eq!(Balances::free_balance(&CHARLIE()), charlie_balance_before);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().scheduled_at,
+			7
+		);
+	})
+}
+
+#[test]
+fn execute_overdue_not_overdue() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			4000,
+			5,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		assert_noop!(
+			PalletSubscription::execute_overdue(Origin::signed(CHARLIE()), 0),
+			Error::<TestRuntime>::SubscriptionNotOverdue
+		);
+
+		// Already paid by the hook
+		run_to_block(2);
+		assert_noop!(
+			PalletSubscription::execute_overdue(Origin::signed(CHARLIE()), 0),
+			Error::<TestRuntime>::SubscriptionNotOverdue
+		);
+	})
+}
+
+#[test]
+fn execute_overdue_unknown_subscription() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			PalletSubscription::execute_overdue(Origin::signed(CHARLIE()), 0),
+			Error::<TestRuntime>::UnknownSubscription
+		);
+	})
+}
+
+#[test]
+fn instalment_left_overdue_when_schedule_full() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		MaxScheduleLookahead::set(&0);
+		let amount = 4000;
+		let frequency = 5;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			frequency,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+		// Block 7 is full by the time the second instalment is scheduled
+		for _ in 0..MaxSubscriptionsPerBlock::get() {
+			assert_ok!(PalletSubscription::subscribe(
+				Origin::signed(CHARLIE()),
+				PAUL(),
+				amount,
+				frequency,
+				None,
+				None,
+				ExistencePolicy::KeepAlive,
+				Some(2 + frequency),
+				false
+			));
+		}
+
+		run_to_block(2);
+		let expected_event = Event::PalletSubscription(crate::Event::InstalmentNotScheduled {
+			subscription_id: 0,
+			due: 2 + frequency,
+		});
+		assert!(System::events().iter().any(|record| record.event == expected_event));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().scheduled_at,
+			2 + frequency
+		);
+		assert!(!PalletSubscription::subscriptions(2 + frequency).contains(&0));
+
+		// A keeper can still execute it once overdue
+		run_to_block(3 + frequency);
+		MaxScheduleLookahead::set(&10);
+		let bob_balance_before = Balances::free_balance(&BOB());
+		assert_ok!(PalletSubscription::execute_overdue(
+			Origin::signed(CHARLIE()),
+			0
+		));
+		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before + amount);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().scheduled_at,
+			3 + frequency * 2
+		);
+	})
+}
+
+#[test]
+fn execute_overdue_rewards_migrated_price() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		KeeperRewardShare::set(&Perbill::from_percent(10));
+		let amount = 4000;
+
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			amount,
+			5,
+			None,
+			0,
+			Vec::new()
+		));
+		assert_ok!(PalletSubscription::subscribe_to_plan(
+			Origin::signed(ALICE()),
+			0
+		));
+		assert_ok!(PalletSubscription::update_plan_price(
+			Origin::signed(BOB()),
+			0,
+			amount * 2,
+			PriceUpdateMode::Migrate
+		));
+
+		// The hook did not run since, the instalment of block 2 is paid after the notice
+		System::set_block_number(2 + PriceChangeNotice::get());
+
+		let bob_balance_before = Balances::free_balance(&BOB());
+		let charlie_balance_before = Balances::free_balance(&CHARLIE());
+		assert_ok!(PalletSubscription::execute_overdue(
+			Origin::signed(CHARLIE()),
+			0
+		));
+
+		assert_eq!(
+			Balances::free_balance(&BOB()),
+			bob_balance_before + amount * 2 - 800
+		);
+		assert_eq!(
+			Balances::free_balance(&CHARLIE()),
+			charlie_balance_before + 800
+		);
+		let expected_event = Event::PalletSubscription(crate::Event::KeeperRewarded {
+			subscription_id: 0,
+			keeper: CHARLIE(),
+			reward: 800,
+		});
+		assert_eq!(System::events().last().unwrap().event, expected_event);
+	})
+}
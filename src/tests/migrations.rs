@@ -0,0 +1,94 @@
+use super::mock::*;
+use crate::{
+	migrations::v1::OldInstalmentData, ExistencePolicy, InstalmentData, SubscriptionStatus,
+};
+use codec::Encode;
+use frame_support::{
+	storage::migration::put_storage_value,
+	traits::{GetStorageVersion, OnRuntimeUpgrade, PalletInfoAccess, StorageVersion},
+	StorageHasher, Twox64Concat,
+};
+
+#[test]
+fn migrate_to_v1() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+		let frequency = 5;
+
+		StorageVersion::new(0).put::<PalletSubscription>();
+		put_storage_value(
+			PalletSubscription::name().as_bytes(),
+			b"Subscriptions",
+			&Twox64Concat::hash(&3u64.encode()),
+			vec![
+				OldInstalmentData {
+					frequency,
+					amount,
+					remaining_payments: Some(2),
+					beneficiary: BOB(),
+					payer: ALICE(),
+				},
+				OldInstalmentData {
+					frequency,
+					amount,
+					remaining_payments: None,
+					beneficiary: BOB(),
+					payer: CHARLIE(),
+				},
+			],
+		);
+
+		PalletSubscription::on_runtime_upgrade();
+
+		assert_eq!(PalletSubscription::on_chain_storage_version(), 1);
+		assert_eq!(PalletSubscription::subscription_nonce(), 2);
+		assert_eq!(PalletSubscription::subscriptions(3).to_vec(), vec![0, 1]);
+		assert_eq!(PalletSubscription::subscriptions_by_payer(ALICE()), vec![0]);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_payer(CHARLIE()),
+			vec![1]
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_beneficiary(BOB()),
+			vec![0, 1]
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0),
+			Some(InstalmentData {
+				frequency,
+				amount,
+				remaining_payments: Some(2),
+				beneficiary: BOB(),
+				payer: ALICE(),
+				scheduled_at: 3,
+				status: SubscriptionStatus::Active,
+				failed_attempts: 0,
+				next_due: 3,
+				prepaid: false,
+				deposit: 0,
+				asset: None,
+				existence_policy: ExistencePolicy::KeepAlive,
+				total_paid: 0,
+				refunded: 0,
+				plan_id: None,
+				price_migration: None,
+			})
+		);
+
+		// Nothing left to migrate
+		PalletSubscription::on_runtime_upgrade();
+		assert_eq!(PalletSubscription::subscription_nonce(), 2);
+
+		// The instalments go on as they would have before the upgrade
+		let bob_balance_before = Balances::free_balance(&BOB());
+		run_to_block(3 + frequency);
+		assert_eq!(
+			Balances::free_balance(&BOB()),
+			bob_balance_before + amount * 4
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().status,
+			SubscriptionStatus::Completed
+		);
+	})
+}
@@ -1,12 +1,13 @@
 use crate::{self as pallet_subscription, Config};
 use frame_support::{
 	construct_runtime, parameter_types,
-	traits::{Everything, OnFinalize, OnInitialize},
+	traits::{ConstU32, ConstU64, Everything, OnFinalize, OnInitialize},
 	weights::{
 		constants::{RocksDbWeight, WEIGHT_PER_SECOND},
 		Weight,
 	},
 };
+use frame_system::EnsureRoot;
 use sp_core::{sr25519, Pair, Public, H256};
 use sp_io::TestExternalities;
 use sp_runtime::{
@@ -28,6 +29,7 @@ construct_runtime!(
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Assets: pallet_assets::{Pallet, Call, Storage, Config<T>, Event<T>},
 		PalletSubscription: pallet_subscription::{Pallet, Call, Storage, Event<T>},
 	}
 );
@@ -66,15 +68,40 @@ impl frame_system::Config for TestRuntime {
 }
 
 parameter_types! {
-	pub MaximumWeight: Weight = Perbill::from_percent(80) *
+	pub const MaxMetadataLength: u32 = 64;
+	pub const MaxSubscriptionsPerBlock: u32 = 5;
+	pub const MaxRetries: u32 = 2;
+	pub const RetryInterval: u64 = 3;
+	pub const MaxStartDelay: u64 = 100;
+	pub const PriceChangeNotice: u64 = 10;
+	pub const ArchivePeriod: u64 = 20;
+	pub storage MaximumWeight: Weight = Perbill::from_percent(80) *
 		BlockWeights::get().max_block;
+	pub storage KeeperRewardShare: Perbill = Perbill::zero();
+	pub storage SubscriptionDeposit: Balance = 0;
+	pub storage PlanDeposit: Balance = 0;
+	pub storage MaxTrialsRemoved: u32 = 10;
+	pub storage MaxScheduleLookahead: u32 = 10;
 }
 
 impl Config for TestRuntime {
+	type ArchivePeriod = ArchivePeriod;
+	type Assets = Assets;
 	type Currency = Balances;
 	type Event = Event;
-	type MaxMetadataLength = ();
+	type KeeperRewardShare = KeeperRewardShare;
+	type MaxMetadataLength = MaxMetadataLength;
+	type MaxRetries = MaxRetries;
+	type MaxScheduleLookahead = MaxScheduleLookahead;
+	type MaxStartDelay = MaxStartDelay;
+	type MaxSubscriptionsPerBlock = MaxSubscriptionsPerBlock;
+	type MaxTrialsRemoved = MaxTrialsRemoved;
 	type MaximumWeight = MaximumWeight;
+	type PlanDeposit = PlanDeposit;
+	type PriceChangeNotice = PriceChangeNotice;
+	type RetryInterval = RetryInterval;
+	type SubscriptionDeposit = SubscriptionDeposit;
+	type WeightInfo = ();
 }
 
 parameter_types! {
@@ -95,6 +122,26 @@ impl pallet_balances::Config for TestRuntime {
 	type WeightInfo = ();
 }
 
+/// Asset the test subscriptions can be paid in
+pub const USD: u32 = 1;
+
+impl pallet_assets::Config for TestRuntime {
+	type ApprovalDeposit = ConstU64<1>;
+	type AssetAccountDeposit = ConstU64<10>;
+	type AssetDeposit = ConstU64<1>;
+	type AssetId = u32;
+	type Balance = Balance;
+	type Currency = Balances;
+	type Event = Event;
+	type Extra = ();
+	type ForceOrigin = EnsureRoot<AccountId>;
+	type Freezer = ();
+	type MetadataDepositBase = ConstU64<1>;
+	type MetadataDepositPerByte = ConstU64<1>;
+	type StringLimit = ConstU32<50>;
+	type WeightInfo = ();
+}
+
 type AccountPublic = <MultiSignature as Verify>::Signer;
 
 /// Helper function to generate a crypto pair from seeds
@@ -133,6 +180,7 @@ pub fn PAUL() -> AccountId {
 
 pub struct ExternalityBuilder {
 	caps_endowed_accounts: Vec<(AccountId, u64)>,
+	asset_endowed_accounts: Vec<(AccountId, u64)>,
 }
 
 /// Mock users AccountId
@@ -146,6 +194,7 @@ impl Default for ExternalityBuilder {
 				(CHARLIE(), 100_000_000_000),
 				(PAUL(), 100_000_000_000),
 			],
+			asset_endowed_accounts: vec![(ALICE(), 1_000_000)],
 		}
 	}
 }
@@ -161,6 +210,18 @@ impl ExternalityBuilder {
 		.assimilate_storage(&mut storage)
 		.unwrap();
 
+		pallet_assets::GenesisConfig::<TestRuntime> {
+			assets: vec![(USD, PAUL(), true, 1)],
+			metadata: vec![],
+			accounts: self
+				.asset_endowed_accounts
+				.into_iter()
+				.map(|(account, balance)| (USD, account, balance))
+				.collect(),
+		}
+		.assimilate_storage(&mut storage)
+		.unwrap();
+
 		let mut ext = TestExternalities::from(storage);
 		ext.execute_with(|| System::set_block_number(1));
 		ext
@@ -1,4 +1,9 @@
+mod amendment;
+mod execute_overdue;
+mod migrations;
 mod on_initialize;
+mod pause;
+mod plans;
 mod subscribe;
 mod unsubscribe;
 
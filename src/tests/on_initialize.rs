@@ -1,6 +1,9 @@
 use super::mock::*;
-use crate::InstalmentData;
-use frame_support::assert_ok;
+use crate::{ExistencePolicy, InstalmentData, SubscriptionStatus, WeightInfo};
+use frame_support::{
+	assert_ok,
+	traits::{Get, OnIdle},
+};
 
 #[test]
 fn trigger_hook_once_transfer_funds() {
@@ -19,6 +22,10 @@ fn trigger_hook_once_transfer_funds() {
 			amount,
 			frequency,
 			recurence,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
 		));
 
 		// Will be executed for the first time at block 2
@@ -28,6 +35,18 @@ fn trigger_hook_once_transfer_funds() {
 			alice_balance_before - amount
 		);
 		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before + amount);
+
+		let expected_event = Event::PalletSubscription(crate::Event::PaymentExecuted {
+			subscription_id: 0,
+			payer: ALICE(),
+			beneficiary: BOB(),
+			amount,
+			remaining_payments: recurence,
+			scheduled_at: Some(2 + frequency),
+		});
+		let received_event = &System::events().last().unwrap().event;
+
+		assert_eq!(*received_event, expected_event);
 	})
 }
 
@@ -48,6 +67,10 @@ fn infinite_subscription() {
 			amount,
 			frequency,
 			recurence,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
 		));
 
 		run_to_block(2 + frequency * 100);
@@ -60,13 +83,29 @@ fn infinite_subscription() {
 			bob_balance_before + amount * 101
 		);
 		let subscriptions_to_come = PalletSubscription::subscriptions(2 + frequency * 101);
-		assert!(subscriptions_to_come.contains(&InstalmentData {
-			frequency,
-			amount,
-			remaining_payments: recurence,
-			beneficiary,
-			payer: ALICE(),
-		},))
+		assert!(subscriptions_to_come.contains(&0));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0),
+			Some(InstalmentData {
+				frequency,
+				amount,
+				remaining_payments: recurence,
+				beneficiary,
+				payer: ALICE(),
+				scheduled_at: 2 + frequency * 101,
+				status: SubscriptionStatus::Active,
+				failed_attempts: 0,
+				next_due: 2 + frequency * 101,
+				prepaid: false,
+				deposit: 0,
+				asset: None,
+				existence_policy: ExistencePolicy::KeepAlive,
+				total_paid: amount * 101,
+				refunded: 0,
+				plan_id: None,
+				price_migration: None,
+			})
+		);
 	})
 }
 
@@ -87,6 +126,10 @@ fn caped_instalment_subscription() {
 			amount,
 			frequency,
 			recurence,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
 		));
 
 		run_to_block(2);
@@ -96,13 +139,29 @@ fn caped_instalment_subscription() {
 		);
 		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before + amount);
 		let subscriptions_to_come = PalletSubscription::subscriptions(2 + frequency);
-		assert!(subscriptions_to_come.contains(&InstalmentData {
-			frequency,
-			amount,
-			remaining_payments: Some(recurence.unwrap() - 1),
-			beneficiary,
-			payer: ALICE(),
-		},));
+		assert!(subscriptions_to_come.contains(&0));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0),
+			Some(InstalmentData {
+				frequency,
+				amount,
+				remaining_payments: Some(recurence.unwrap() - 1),
+				beneficiary,
+				payer: ALICE(),
+				scheduled_at: 2 + frequency,
+				status: SubscriptionStatus::Active,
+				failed_attempts: 0,
+				next_due: 2 + frequency,
+				prepaid: false,
+				deposit: 0,
+				asset: None,
+				existence_policy: ExistencePolicy::KeepAlive,
+				total_paid: amount,
+				refunded: 0,
+				plan_id: None,
+				price_migration: None,
+			})
+		);
 
 		run_to_block(2 + frequency * (recurence.unwrap() as u64 - 1));
 		assert_eq!(
@@ -115,7 +174,55 @@ fn caped_instalment_subscription() {
 		);
 		let subscriptions_to_come =
 			PalletSubscription::subscriptions(2 + frequency * recurence.unwrap() as u64);
-		assert!(subscriptions_to_come.is_empty())
+		assert!(subscriptions_to_come.is_empty());
+
+		let events = System::events();
+		let received_events: Vec<_> =
+			events[events.len() - 2..].iter().map(|record| record.event.clone()).collect();
+		assert_eq!(
+			received_events,
+			vec![
+				Event::PalletSubscription(crate::Event::PaymentExecuted {
+					subscription_id: 0,
+					payer: ALICE(),
+					beneficiary: BOB(),
+					amount,
+					remaining_payments: Some(0),
+					scheduled_at: None,
+				}),
+				Event::PalletSubscription(crate::Event::SubscriptionCompleted {
+					subscription_id: 0,
+					payer: ALICE(),
+					beneficiary: BOB(),
+				}),
+			]
+		);
+
+		// The subscription is archived
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0),
+			Some(InstalmentData {
+				frequency,
+				amount,
+				remaining_payments: Some(0),
+				beneficiary: BOB(),
+				payer: ALICE(),
+				scheduled_at: 2 + frequency * (recurence.unwrap() as u64 - 1),
+				status: SubscriptionStatus::Completed,
+				failed_attempts: 0,
+				next_due: 2 + frequency * (recurence.unwrap() as u64 - 1),
+				prepaid: false,
+				deposit: 0,
+				asset: None,
+				existence_policy: ExistencePolicy::KeepAlive,
+				total_paid: amount * recurence.unwrap() as u64,
+				refunded: 0,
+				plan_id: None,
+				price_migration: None,
+			})
+		);
+		assert!(PalletSubscription::subscriptions_by_payer(ALICE()).is_empty());
+		assert!(PalletSubscription::subscriptions_by_beneficiary(BOB()).is_empty());
 	})
 }
 
@@ -136,6 +243,10 @@ fn transfer_failed() {
 			amount,
 			frequency,
 			recurence,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
 		));
 
 		// First execution work like a charm
@@ -154,8 +265,331 @@ fn transfer_failed() {
 		);
 		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before + amount);
 
+		let retry_at = 2 + frequency + RetryInterval::get();
+		let events = System::events();
+		let received_events: Vec<_> =
+			events[events.len() - 2..].iter().map(|record| record.event.clone()).collect();
+		assert_eq!(
+			received_events,
+			vec![
+				Event::PalletSubscription(crate::Event::PaymentFailed {
+					subscription_id: 0,
+					error: pallet_balances::Error::<TestRuntime>::InsufficientBalance.into(),
+				}),
+				Event::PalletSubscription(crate::Event::PaymentRetryScheduled {
+					subscription_id: 0,
+					attempt: 1,
+					retry_at,
+				}),
+			]
+		);
+
+		// Kept on record and retried a bit later
+		assert!(PalletSubscription::subscriptions(retry_at).contains(&0));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0),
+			Some(InstalmentData {
+				frequency,
+				amount,
+				remaining_payments: recurence,
+				beneficiary: BOB(),
+				payer: ALICE(),
+				scheduled_at: retry_at,
+				status: SubscriptionStatus::Delinquent,
+				failed_attempts: 1,
+				next_due: 2,
+				prepaid: false,
+				deposit: 0,
+				asset: None,
+				existence_policy: ExistencePolicy::KeepAlive,
+				total_paid: amount,
+				refunded: 0,
+				plan_id: None,
+				price_migration: None,
+			})
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_payer(ALICE()).to_vec(),
+			vec![0]
+		);
+
+		// Every retry fails, the subscription ends up cancelled
+		run_to_block(2 + frequency + RetryInterval::get() * (MaxRetries::get() as u64 + 1));
+		assert_eq!(
+			Balances::free_balance(&ALICE()),
+			alice_balance_before - amount
+		);
+
+		let expected_event =
+			Event::PalletSubscription(crate::Event::SubscriptionCancelled { subscription_id: 0 });
+		let received_event = &System::events().last().unwrap().event;
+
+		assert_eq!(*received_event, expected_event);
+
 		// Won't be executed anymore
 		let subscriptions_to_come = PalletSubscription::subscriptions(2 + frequency * 10);
-		assert!(subscriptions_to_come.is_empty())
+		assert!(subscriptions_to_come.is_empty());
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().status,
+			SubscriptionStatus::Cancelled
+		);
+		assert!(PalletSubscription::subscriptions_by_payer(ALICE()).is_empty());
+	})
+}
+
+#[test]
+fn transfer_failed_then_recovered() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let alice_balance_before = Balances::free_balance(&ALICE());
+
+		let amount = alice_balance_before / 2 + 1;
+		let frequency = 5;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			frequency,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		// Second instalment fails
+		run_to_block(2 + frequency);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().status,
+			SubscriptionStatus::Delinquent
+		);
+
+		// ALICE tops up the account before the retry
+		assert_ok!(Balances::transfer(
+			Origin::signed(CHARLIE()),
+			ALICE(),
+			amount
+		));
+
+		let retry_at = 2 + frequency + RetryInterval::get();
+		run_to_block(retry_at);
+		assert_eq!(
+			Balances::free_balance(&ALICE()),
+			alice_balance_before - amount
+		);
+
+		let expected_event =
+			Event::PalletSubscription(crate::Event::PaymentRecovered { subscription_id: 0 });
+		assert!(System::events().iter().any(|record| record.event == expected_event));
+
+		let subscription = PalletSubscription::subscriptions_by_id(0).unwrap();
+		assert_eq!(subscription.status, SubscriptionStatus::Active);
+		assert_eq!(subscription.failed_attempts, 0);
+		// The retry does not shift the billing cadence
+		assert_eq!(subscription.next_due, 2 + frequency * 2);
+		assert!(PalletSubscription::subscriptions(2 + frequency * 2).contains(&0));
+	})
+}
+
+#[test]
+fn deferred_instalments_are_processed_first() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		// Only two instalments fit in a block
+		MaximumWeight::set(&<() as WeightInfo>::on_initialize(2));
+
+		// Ids 0 to 4 fill block 2, ids 5 to 7 overflow to block 3
+		for _ in 0..8 {
+			assert_ok!(PalletSubscription::subscribe(
+				Origin::signed(ALICE()),
+				BOB(),
+				4000,
+				100,
+				None,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
+			));
+		}
+		assert_eq!(
+			PalletSubscription::subscriptions(2).to_vec(),
+			vec![0, 1, 2, 3, 4]
+		);
+		assert_eq!(PalletSubscription::subscriptions(3).to_vec(), vec![5, 6, 7]);
+
+		let paid_at = |block: u64| -> Vec<u64> {
+			run_to_block(block);
+			System::events()
+				.into_iter()
+				.filter_map(|record| match record.event {
+					Event::PalletSubscription(crate::Event::PaymentExecuted {
+						subscription_id,
+						..
+					}) => Some(subscription_id),
+					_ => None,
+				})
+				.collect()
+		};
+
+		assert_eq!(paid_at(2), vec![0, 1]);
+		// The leftovers keep their place and block 2 is resumed first
+		assert_eq!(PalletSubscription::subscriptions(2).to_vec(), vec![2, 3, 4]);
+		assert_eq!(PalletSubscription::incomplete_since(), Some(2));
+
+		assert_eq!(paid_at(3), vec![0, 1, 2, 3]);
+		assert_eq!(PalletSubscription::subscriptions(3).to_vec(), vec![5, 6, 7]);
+
+		let expected_event = Event::PalletSubscription(crate::Event::PaymentDelayed {
+			subscription_id: 2,
+			delay: 1,
+		});
+		assert!(System::events().iter().any(|record| record.event == expected_event));
+
+		assert_eq!(paid_at(4), vec![0, 1, 2, 3, 4, 5]);
+		assert_eq!(paid_at(5), vec![0, 1, 2, 3, 4, 5, 6, 7]);
+		assert!(PalletSubscription::subscriptions(3).is_empty());
+
+		// Every instalment has been caught up with
+		run_to_block(6);
+		assert_eq!(PalletSubscription::incomplete_since(), None);
+	})
+}
+
+#[test]
+fn deferred_instalment_keeps_its_cadence() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		// Only two instalments fit in a block
+		MaximumWeight::set(&<() as WeightInfo>::on_initialize(2));
+		let frequency = 10;
+
+		for _ in 0..3 {
+			assert_ok!(PalletSubscription::subscribe(
+				Origin::signed(ALICE()),
+				BOB(),
+				4000,
+				frequency,
+				None,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
+			));
+		}
+
+		// The last subscription is paid a block late but stays due every 10 blocks from block 2
+		run_to_block(3);
+		let subscription = PalletSubscription::subscriptions_by_id(2).unwrap();
+		assert_eq!(subscription.next_due, 2 + frequency);
+		assert_eq!(subscription.scheduled_at, 2 + frequency);
+		assert_eq!(
+			PalletSubscription::subscriptions(2 + frequency).to_vec(),
+			vec![0, 1, 2]
+		);
+
+		// Deferred again, then back on schedule
+		run_to_block(3 + frequency);
+		let subscription = PalletSubscription::subscriptions_by_id(2).unwrap();
+		assert_eq!(subscription.next_due, 2 + frequency * 2);
+		assert!(PalletSubscription::subscriptions(2 + frequency * 2).contains(&2));
+	})
+}
+
+#[test]
+fn leftover_instalments_are_processed_on_idle() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		// Only two instalments fit in a block
+		MaximumWeight::set(&<() as WeightInfo>::on_initialize(2));
+
+		for _ in 0..5 {
+			assert_ok!(PalletSubscription::subscribe(
+				Origin::signed(ALICE()),
+				BOB(),
+				4000,
+				100,
+				None,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
+			));
+		}
+
+		run_to_block(2);
+		assert_eq!(PalletSubscription::subscriptions(2).to_vec(), vec![2, 3, 4]);
+
+		// Not enough weight left for a single instalment
+		assert_eq!(PalletSubscription::on_idle(2, 0), 0);
+		assert_eq!(PalletSubscription::subscriptions(2).to_vec(), vec![2, 3, 4]);
+
+		// The rest of the block drains the backlog
+		let remaining_weight = <() as WeightInfo>::on_initialize(10);
+		let used_weight = PalletSubscription::on_idle(2, remaining_weight);
+		assert!(used_weight <= remaining_weight);
+		assert!(PalletSubscription::subscriptions(2).is_empty());
+		assert_eq!(PalletSubscription::incomplete_since(), None);
+		for subscription_id in 0..5 {
+			assert_eq!(
+				PalletSubscription::subscriptions_by_id(subscription_id).unwrap().scheduled_at,
+				102
+			);
+		}
+
+		// With no backlog only `IncompleteSince` is looked at
+		let since_weight =
+			<TestRuntime as frame_system::Config>::DbWeight::get().reads_writes(1, 1);
+		assert_eq!(
+			PalletSubscription::on_idle(2, remaining_weight),
+			since_weight
+		);
+	})
+}
+
+#[test]
+fn allow_death_drains_payer() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = Balances::free_balance(&ALICE());
+		let bob_balance_before = Balances::free_balance(&BOB());
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			5,
+			Some(1),
+			None,
+			ExistencePolicy::AllowDeath,
+			None,
+			false
+		));
+
+		run_to_block(2);
+		assert_eq!(Balances::free_balance(&ALICE()), 0);
+		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before + amount);
+	})
+}
+
+#[test]
+fn keep_alive_fails_to_drain_payer() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = Balances::free_balance(&ALICE());
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			5,
+			Some(1),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		run_to_block(2);
+		assert_eq!(Balances::free_balance(&ALICE()), amount);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().status,
+			SubscriptionStatus::Delinquent
+		);
 	})
 }
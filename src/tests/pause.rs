@@ -0,0 +1,145 @@
+use super::mock::*;
+use crate::{Error, ExistencePolicy, SubscriptionStatus};
+use frame_support::{assert_noop, assert_ok, traits::Get};
+
+#[test]
+fn pause_and_resume() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+		let frequency = 5;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			frequency,
+			Some(3),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+		run_to_block(2);
+
+		assert_ok!(PalletSubscription::pause(Origin::signed(ALICE()), 0));
+		assert!(PalletSubscription::subscriptions(2 + frequency).is_empty());
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().status,
+			SubscriptionStatus::Paused
+		);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::PalletSubscription(crate::Event::SubscriptionPaused { subscription_id: 0 })
+		);
+
+		// Nothing is paid while paused
+		let bob_balance_before = Balances::free_balance(&BOB());
+		run_to_block(20);
+		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before);
+
+		assert_ok!(PalletSubscription::resume(Origin::signed(ALICE()), 0));
+		assert!(PalletSubscription::subscriptions(21).contains(&0));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::PalletSubscription(crate::Event::SubscriptionResumed {
+				subscription_id: 0,
+				scheduled_at: 21,
+			})
+		);
+
+		let subscription = PalletSubscription::subscriptions_by_id(0).unwrap();
+		assert_eq!(subscription.status, SubscriptionStatus::Active);
+		assert_eq!(subscription.remaining_payments, Some(2));
+		assert_eq!(subscription.next_due, 21);
+
+		run_to_block(21 + frequency);
+		assert_eq!(
+			Balances::free_balance(&BOB()),
+			bob_balance_before + amount * 2
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().status,
+			SubscriptionStatus::Completed
+		);
+	})
+}
+
+#[test]
+fn pause_wrong_caller_or_state() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			4000,
+			5,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		assert_noop!(
+			PalletSubscription::pause(Origin::signed(BOB()), 0),
+			Error::<TestRuntime>::CallerIsNotSubscriber
+		);
+		assert_noop!(
+			PalletSubscription::resume(Origin::signed(ALICE()), 0),
+			Error::<TestRuntime>::SubscriptionIsNotPaused
+		);
+
+		assert_ok!(PalletSubscription::pause(Origin::signed(ALICE()), 0));
+		assert_noop!(
+			PalletSubscription::pause(Origin::signed(ALICE()), 0),
+			Error::<TestRuntime>::SubscriptionIsPaused
+		);
+		assert_noop!(
+			PalletSubscription::resume(Origin::signed(BOB()), 0),
+			Error::<TestRuntime>::CallerIsNotSubscriber
+		);
+
+		// A paused subscription is never overdue
+		run_to_block(3);
+		assert_noop!(
+			PalletSubscription::execute_overdue(Origin::signed(CHARLIE()), 0),
+			Error::<TestRuntime>::SubscriptionIsPaused
+		);
+	})
+}
+
+#[test]
+fn pause_delinquent_subscription() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = Balances::free_balance(&ALICE()) / 2 + 1;
+		let frequency = 5;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			frequency,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		// Second instalment fails, pausing cannot reset the retries
+		run_to_block(2 + frequency);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().status,
+			SubscriptionStatus::Delinquent
+		);
+		assert_noop!(
+			PalletSubscription::pause(Origin::signed(ALICE()), 0),
+			Error::<TestRuntime>::SubscriptionIsDelinquent
+		);
+
+		// Cancelled once the retries are exhausted
+		run_to_block(2 + frequency + RetryInterval::get() * (MaxRetries::get() as u64 + 1));
+		let expected_event =
+			Event::PalletSubscription(crate::Event::SubscriptionCancelled { subscription_id: 0 });
+		assert!(System::events().iter().any(|record| record.event == expected_event));
+	})
+}
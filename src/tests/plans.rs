@@ -0,0 +1,700 @@
+use super::mock::*;
+use crate::{
+	Error, ExistencePolicy, InstalmentData, Plan, PriceUpdateMode, ReservableCurrency,
+	SubscriptionStatus, TrialsUsed,
+};
+use frame_support::{assert_noop, assert_ok, traits::Get, BoundedVec};
+
+#[test]
+fn create_plan() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+		let frequency = 5;
+		let number_of_installment = Some(12);
+
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			amount,
+			frequency,
+			number_of_installment,
+			0,
+			Vec::new()
+		));
+
+		let expected_plan = Plan {
+			frequency,
+			amount,
+			remaining_payments: number_of_installment,
+			beneficiary: BOB(),
+			trial: 0,
+			deposit: 0,
+		};
+		assert_eq!(
+			PalletSubscription::subscription_plans(0),
+			Some(expected_plan.clone())
+		);
+		assert_eq!(PalletSubscription::plan_nonce(), 1);
+
+		let expected_event = Event::PalletSubscription(crate::Event::PlanCreated(0, expected_plan));
+		let received_event = &System::events()[0].event;
+
+		assert_eq!(*received_event, expected_event);
+
+		// A second plan gets a fresh id
+
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			amount * 10,
+			frequency * 12,
+			None,
+			0,
+			Vec::new()
+		));
+		assert!(PalletSubscription::subscription_plans(1).is_some());
+		assert_eq!(PalletSubscription::plan_nonce(), 2);
+	})
+}
+
+#[test]
+fn create_plan_invalid_terms() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			PalletSubscription::create_plan(Origin::signed(BOB()), 0, 5, None, 0, Vec::new()),
+			Error::<TestRuntime>::InvalidSubscription
+		);
+		assert_noop!(
+			PalletSubscription::create_plan(Origin::signed(BOB()), 4000, 0, None, 0, Vec::new()),
+			Error::<TestRuntime>::InvalidSubscription
+		);
+		assert_noop!(
+			PalletSubscription::create_plan(Origin::signed(BOB()), 4000, 5, Some(0), 0, Vec::new()),
+			Error::<TestRuntime>::InvalidSubscription
+		);
+	})
+}
+
+#[test]
+fn subscribe_to_plan() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+		let frequency = 5;
+		let number_of_installment = Some(12);
+
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			amount,
+			frequency,
+			number_of_installment,
+			0,
+			Vec::new()
+		));
+
+		assert_ok!(PalletSubscription::subscribe_to_plan(
+			Origin::signed(ALICE()),
+			0
+		));
+
+		let expected_instalment = InstalmentData {
+			frequency,
+			amount,
+			remaining_payments: number_of_installment,
+			beneficiary: BOB(),
+			payer: ALICE(),
+			scheduled_at: 2,
+			status: SubscriptionStatus::Active,
+			failed_attempts: 0,
+			next_due: 2,
+			prepaid: false,
+			deposit: 0,
+			asset: None,
+			existence_policy: ExistencePolicy::KeepAlive,
+			total_paid: 0,
+			refunded: 0,
+			plan_id: Some(0),
+			price_migration: None,
+		};
+		assert!(PalletSubscription::subscriptions(2).contains(&0));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0),
+			Some(expected_instalment.clone())
+		);
+
+		let expected_event =
+			Event::PalletSubscription(crate::Event::Subscription(0, expected_instalment));
+		let received_event = &System::events()[1].event;
+
+		assert_eq!(*received_event, expected_event);
+	})
+}
+
+#[test]
+fn subscribe_to_unknown_plan() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			PalletSubscription::subscribe_to_plan(Origin::signed(ALICE()), 0),
+			Error::<TestRuntime>::UnknownPlan
+		);
+	})
+}
+
+#[test]
+fn subscribe_to_own_plan() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			4000,
+			5,
+			None,
+			0,
+			Vec::new()
+		));
+
+		assert_noop!(
+			PalletSubscription::subscribe_to_plan(Origin::signed(BOB()), 0),
+			Error::<TestRuntime>::InvalidSubscription
+		);
+	})
+}
+
+#[test]
+fn plan_metadata() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let metadata = b"Premium monthly".to_vec();
+
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			4000,
+			5,
+			None,
+			0,
+			metadata.clone()
+		));
+
+		let bounded_metadata: BoundedVec<u8, MaxMetadataLength> = metadata.try_into().unwrap();
+		assert_eq!(
+			PalletSubscription::plan_metadata(0),
+			Some(bounded_metadata.clone())
+		);
+
+		let expected_event =
+			Event::PalletSubscription(crate::Event::PlanMetadataSet(0, bounded_metadata));
+		let received_event = &System::events()[1].event;
+
+		assert_eq!(*received_event, expected_event);
+
+		// The owner can update it
+
+		let metadata = b"ipfs://plan-catalog/premium".to_vec();
+
+		assert_ok!(PalletSubscription::set_plan_metadata(
+			Origin::signed(BOB()),
+			0,
+			metadata.clone()
+		));
+
+		let bounded_metadata: BoundedVec<u8, MaxMetadataLength> = metadata.try_into().unwrap();
+		assert_eq!(
+			PalletSubscription::plan_metadata(0),
+			Some(bounded_metadata.clone())
+		);
+
+		let expected_event =
+			Event::PalletSubscription(crate::Event::PlanMetadataSet(0, bounded_metadata));
+		let received_event = &System::events()[2].event;
+
+		assert_eq!(*received_event, expected_event);
+
+		// An empty blob clears it
+
+		assert_ok!(PalletSubscription::set_plan_metadata(
+			Origin::signed(BOB()),
+			0,
+			Vec::new()
+		));
+		assert_eq!(PalletSubscription::plan_metadata(0), None);
+		assert_eq!(System::events().len(), 4);
+	})
+}
+
+#[test]
+fn plan_metadata_too_long() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let metadata = vec![0u8; MaxMetadataLength::get() as usize + 1];
+
+		assert_noop!(
+			PalletSubscription::create_plan(
+				Origin::signed(BOB()),
+				4000,
+				5,
+				None,
+				0,
+				metadata.clone()
+			),
+			Error::<TestRuntime>::MetadataTooLong
+		);
+
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			4000,
+			5,
+			None,
+			0,
+			Vec::new()
+		));
+		assert_noop!(
+			PalletSubscription::set_plan_metadata(Origin::signed(BOB()), 0, metadata),
+			Error::<TestRuntime>::MetadataTooLong
+		);
+	})
+}
+
+#[test]
+fn set_plan_metadata_not_owner() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			4000,
+			5,
+			None,
+			0,
+			Vec::new()
+		));
+
+		assert_noop!(
+			PalletSubscription::set_plan_metadata(Origin::signed(ALICE()), 0, b"scam".to_vec()),
+			Error::<TestRuntime>::CallerIsNotPlanOwner
+		);
+		assert_noop!(
+			PalletSubscription::set_plan_metadata(Origin::signed(BOB()), 1, b"nope".to_vec()),
+			Error::<TestRuntime>::UnknownPlan
+		);
+	})
+}
+
+#[test]
+fn subscribe_to_plan_with_trial() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let trial = 20;
+
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			4000,
+			5,
+			None,
+			trial,
+			Vec::new()
+		));
+
+		assert_ok!(PalletSubscription::subscribe_to_plan(
+			Origin::signed(ALICE()),
+			0
+		));
+		assert!(PalletSubscription::subscriptions(2 + trial).contains(&0));
+		assert!(PalletSubscription::trials_used(0, ALICE()).is_some());
+
+		let expected_event = Event::PalletSubscription(crate::Event::TrialStarted {
+			plan_id: 0,
+			subscription_id: 0,
+			payer: ALICE(),
+			first_charge_at: 2 + trial,
+		});
+		assert_eq!(System::events().last().unwrap().event, expected_event);
+
+		// No second trial once unsubscribed
+		assert_ok!(PalletSubscription::unsubscribe_by_id(
+			Origin::signed(ALICE()),
+			0
+		));
+		assert_ok!(PalletSubscription::subscribe_to_plan(
+			Origin::signed(ALICE()),
+			0
+		));
+		assert!(PalletSubscription::subscriptions(2).contains(&1));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(1).unwrap().next_due,
+			2
+		);
+	})
+}
+
+#[test]
+fn create_plan_trial_too_long() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			PalletSubscription::create_plan(
+				Origin::signed(BOB()),
+				4000,
+				5,
+				None,
+				MaxStartDelay::get(),
+				Vec::new()
+			),
+			Error::<TestRuntime>::TrialTooLong
+		);
+	})
+}
+
+#[test]
+fn update_plan_price_grandfather() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			amount,
+			5,
+			None,
+			0,
+			Vec::new()
+		));
+		assert_ok!(PalletSubscription::subscribe_to_plan(
+			Origin::signed(ALICE()),
+			0
+		));
+
+		assert_noop!(
+			PalletSubscription::update_plan_price(
+				Origin::signed(ALICE()),
+				0,
+				amount * 2,
+				PriceUpdateMode::Grandfather
+			),
+			Error::<TestRuntime>::CallerIsNotPlanOwner
+		);
+		assert_ok!(PalletSubscription::update_plan_price(
+			Origin::signed(BOB()),
+			0,
+			amount * 2,
+			PriceUpdateMode::Grandfather
+		));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::PalletSubscription(crate::Event::PlanPriceUpdated {
+				plan_id: 0,
+				old_amount: amount,
+				new_amount: amount * 2,
+				mode: PriceUpdateMode::Grandfather,
+				effective_at: None,
+			})
+		);
+
+		// New subscribers pay the new price, the existing one keeps the old one
+		assert_ok!(PalletSubscription::subscribe_to_plan(
+			Origin::signed(CHARLIE()),
+			0
+		));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(1).unwrap().amount,
+			amount * 2
+		);
+
+		run_to_block(50);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().amount,
+			amount
+		);
+	})
+}
+
+#[test]
+fn update_plan_price_migrate() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+		let frequency = 5;
+
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			amount,
+			frequency,
+			None,
+			0,
+			Vec::new()
+		));
+		assert_ok!(PalletSubscription::subscribe_to_plan(
+			Origin::signed(ALICE()),
+			0
+		));
+		run_to_block(2);
+
+		assert_ok!(PalletSubscription::update_plan_price(
+			Origin::signed(BOB()),
+			0,
+			amount * 2,
+			PriceUpdateMode::Migrate
+		));
+		let effective_at = 2 + PriceChangeNotice::get();
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::PalletSubscription(crate::Event::PlanPriceUpdated {
+				plan_id: 0,
+				old_amount: amount,
+				new_amount: amount * 2,
+				mode: PriceUpdateMode::Migrate,
+				effective_at: Some(effective_at),
+			})
+		);
+
+		// The old price still applies during the notice
+		let bob_balance_before = Balances::free_balance(&BOB());
+		run_to_block(effective_at - 1);
+		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before + amount);
+
+		// Instalments of blocks 12 and 17 are paid at the new price
+		run_to_block(effective_at + frequency);
+		assert_eq!(
+			Balances::free_balance(&BOB()),
+			bob_balance_before + amount * 5
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().amount,
+			amount * 2
+		);
+		// The only subscriber went through the migration
+		assert!(PalletSubscription::plan_price_migrations(0).is_none());
+
+		let expected_event = Event::PalletSubscription(crate::Event::SubscriptionPriceMigrated {
+			subscription_id: 0,
+			plan_id: 0,
+			amount: amount * 2,
+		});
+		assert!(System::events().iter().any(|record| record.event == expected_event));
+	})
+}
+
+#[test]
+fn update_plan_price_grandfather_clears_migration() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			amount,
+			5,
+			None,
+			0,
+			Vec::new()
+		));
+		assert_ok!(PalletSubscription::subscribe_to_plan(
+			Origin::signed(ALICE()),
+			0
+		));
+
+		assert_ok!(PalletSubscription::update_plan_price(
+			Origin::signed(BOB()),
+			0,
+			amount * 2,
+			PriceUpdateMode::Migrate
+		));
+		assert_eq!(
+			PalletSubscription::plan_price_migrations(0).unwrap().pending,
+			1
+		);
+		assert_ok!(PalletSubscription::update_plan_price(
+			Origin::signed(BOB()),
+			0,
+			amount * 3,
+			PriceUpdateMode::Grandfather
+		));
+		assert!(PalletSubscription::plan_price_migrations(0).is_none());
+
+		run_to_block(50);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().amount,
+			amount
+		);
+	})
+}
+
+#[test]
+fn update_plan_price_migrate_skips_amended() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+		let frequency = 5;
+
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			amount,
+			frequency,
+			None,
+			0,
+			Vec::new()
+		));
+		assert_ok!(PalletSubscription::subscribe_to_plan(
+			Origin::signed(ALICE()),
+			0
+		));
+		assert_ok!(PalletSubscription::subscribe_to_plan(
+			Origin::signed(CHARLIE()),
+			0
+		));
+		assert_eq!(PalletSubscription::plan_subscription_count(0), 2);
+
+		assert_ok!(PalletSubscription::update_plan_price(
+			Origin::signed(BOB()),
+			0,
+			amount * 2,
+			PriceUpdateMode::Migrate
+		));
+		assert_eq!(
+			PalletSubscription::plan_price_migrations(0).unwrap().pending,
+			2
+		);
+
+		// The terms agreed on after the price change are kept
+		assert_ok!(PalletSubscription::propose_amendment(
+			Origin::signed(BOB()),
+			0,
+			amount + 500,
+			frequency,
+			None
+		));
+		assert_ok!(PalletSubscription::accept_amendment(
+			Origin::signed(ALICE()),
+			0,
+			amount + 500,
+			frequency,
+			None
+		));
+		assert_eq!(
+			PalletSubscription::plan_price_migrations(0).unwrap().pending,
+			1
+		);
+
+		run_to_block(1 + PriceChangeNotice::get() + frequency);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().amount,
+			amount + 500
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(1).unwrap().amount,
+			amount * 2
+		);
+		assert!(PalletSubscription::plan_price_migrations(0).is_none());
+	})
+}
+
+#[test]
+fn update_plan_price_migration_removed_with_last_subscriber() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			amount,
+			5,
+			None,
+			0,
+			Vec::new()
+		));
+
+		// Nobody to migrate
+		assert_ok!(PalletSubscription::update_plan_price(
+			Origin::signed(BOB()),
+			0,
+			amount * 2,
+			PriceUpdateMode::Migrate
+		));
+		assert!(PalletSubscription::plan_price_migrations(0).is_none());
+
+		assert_ok!(PalletSubscription::subscribe_to_plan(
+			Origin::signed(ALICE()),
+			0
+		));
+		assert_ok!(PalletSubscription::update_plan_price(
+			Origin::signed(BOB()),
+			0,
+			amount * 3,
+			PriceUpdateMode::Migrate
+		));
+		assert!(PalletSubscription::plan_price_migrations(0).is_some());
+
+		assert_ok!(PalletSubscription::unsubscribe_by_id(
+			Origin::signed(ALICE()),
+			0
+		));
+		assert!(PalletSubscription::plan_price_migrations(0).is_none());
+		assert_eq!(PalletSubscription::plan_subscription_count(0), 0);
+	})
+}
+
+#[test]
+fn remove_plan() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+		let deposit = 500;
+		PlanDeposit::set(&deposit);
+
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			amount,
+			5,
+			None,
+			0,
+			b"metadata".to_vec()
+		));
+		assert_eq!(Balances::reserved_balance(&BOB()), deposit);
+		assert_ok!(PalletSubscription::subscribe_to_plan(
+			Origin::signed(ALICE()),
+			0
+		));
+
+		assert_noop!(
+			PalletSubscription::remove_plan(Origin::signed(ALICE()), 0),
+			Error::<TestRuntime>::CallerIsNotPlanOwner
+		);
+		assert_ok!(PalletSubscription::remove_plan(Origin::signed(BOB()), 0));
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::PalletSubscription(crate::Event::PlanRemoved { plan_id: 0 })
+		);
+		assert!(PalletSubscription::subscription_plans(0).is_none());
+		assert!(PalletSubscription::plan_metadata(0).is_none());
+		assert_eq!(Balances::reserved_balance(&BOB()), 0);
+
+		// The existing subscriber is still paying, nobody else can subscribe
+		assert_noop!(
+			PalletSubscription::subscribe_to_plan(Origin::signed(CHARLIE()), 0),
+			Error::<TestRuntime>::UnknownPlan
+		);
+		assert_noop!(
+			PalletSubscription::remove_plan(Origin::signed(BOB()), 0),
+			Error::<TestRuntime>::UnknownPlan
+		);
+		let bob_balance_before = Balances::free_balance(&BOB());
+		run_to_block(3);
+		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before + amount);
+
+		assert_ok!(PalletSubscription::unsubscribe_by_id(
+			Origin::signed(ALICE()),
+			0
+		));
+		assert_eq!(PalletSubscription::plan_subscription_count(0), 0);
+	})
+}
+
+#[test]
+fn remove_plan_clears_trials() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_ok!(PalletSubscription::create_plan(
+			Origin::signed(BOB()),
+			4000,
+			5,
+			None,
+			10,
+			Vec::new()
+		));
+		for payer in [ALICE(), CHARLIE(), PAUL()] {
+			assert_ok!(PalletSubscription::subscribe_to_plan(
+				Origin::signed(payer),
+				0
+			));
+		}
+		assert_eq!(TrialsUsed::<TestRuntime>::iter_prefix(0).count(), 3);
+
+		// Only up to the limit
+		MaxTrialsRemoved::set(&2);
+		assert_ok!(PalletSubscription::remove_plan(Origin::signed(BOB()), 0));
+		assert_eq!(TrialsUsed::<TestRuntime>::iter_prefix(0).count(), 1);
+		assert_eq!(PalletSubscription::plan_subscription_count(0), 0);
+	})
+}
@@ -1,6 +1,6 @@
 use super::mock::*;
-use crate::{Error, InstalmentData};
-use frame_support::{assert_noop, assert_ok};
+use crate::{Error, ExistencePolicy, InstalmentData, ReservableCurrency, SubscriptionStatus};
+use frame_support::{assert_noop, assert_ok, traits::Get};
 
 #[test]
 fn subscribe() {
@@ -14,7 +14,11 @@ fn subscribe() {
 			BOB(),
 			amount,
 			frequency,
-			number_of_installment
+			number_of_installment,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
 		));
 
 		let expected_instalment = InstalmentData {
@@ -23,11 +27,27 @@ fn subscribe() {
 			remaining_payments: number_of_installment,
 			beneficiary: BOB(),
 			payer: ALICE(),
+			scheduled_at: 2,
+			status: SubscriptionStatus::Active,
+			failed_attempts: 0,
+			next_due: 2,
+			prepaid: false,
+			deposit: 0,
+			asset: None,
+			existence_policy: ExistencePolicy::KeepAlive,
+			total_paid: 0,
+			refunded: 0,
+			plan_id: None,
+			price_migration: None,
 		};
-		assert!(PalletSubscription::subscriptions(2).contains(&expected_instalment));
+		assert!(PalletSubscription::subscriptions(2).contains(&0));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0),
+			Some(expected_instalment.clone())
+		);
 
 		let expected_event =
-			Event::PalletSubscription(crate::Event::Subscription(expected_instalment));
+			Event::PalletSubscription(crate::Event::Subscription(0, expected_instalment));
 		let received_event = &System::events()[0].event;
 
 		assert_eq!(*received_event, expected_event);
@@ -44,7 +64,11 @@ fn subscribe() {
 				BOB(),
 				amount,
 				frequency,
-				number_of_installment
+				number_of_installment,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
 			),
 			Error::<TestRuntime>::InvalidSubscription
 		);
@@ -61,7 +85,11 @@ fn subscribe() {
 				BOB(),
 				amount,
 				frequency,
-				number_of_installment
+				number_of_installment,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
 			),
 			Error::<TestRuntime>::InvalidSubscription
 		);
@@ -78,7 +106,11 @@ fn subscribe() {
 				BOB(),
 				amount,
 				frequency,
-				number_of_installment
+				number_of_installment,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
 			),
 			Error::<TestRuntime>::InvalidSubscription
 		);
@@ -102,7 +134,11 @@ fn subscribe_multiple_events() {
 			BOB(),
 			amount,
 			frequency,
-			number_of_installment
+			number_of_installment,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
 		));
 
 		let expected_instalment = InstalmentData {
@@ -111,11 +147,27 @@ fn subscribe_multiple_events() {
 			remaining_payments: number_of_installment,
 			beneficiary: BOB(),
 			payer: ALICE(),
+			scheduled_at: 2,
+			status: SubscriptionStatus::Active,
+			failed_attempts: 0,
+			next_due: 2,
+			prepaid: false,
+			deposit: 0,
+			asset: None,
+			existence_policy: ExistencePolicy::KeepAlive,
+			total_paid: 0,
+			refunded: 0,
+			plan_id: None,
+			price_migration: None,
 		};
-		assert!(PalletSubscription::subscriptions(2).contains(&expected_instalment));
+		assert!(PalletSubscription::subscriptions(2).contains(&0));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0),
+			Some(expected_instalment.clone())
+		);
 
 		let expected_event =
-			Event::PalletSubscription(crate::Event::Subscription(expected_instalment));
+			Event::PalletSubscription(crate::Event::Subscription(0, expected_instalment));
 		let received_event = &System::events()[0].event;
 
 		assert_eq!(*received_event, expected_event);
@@ -131,7 +183,11 @@ fn subscribe_multiple_events() {
 			PAUL(),
 			amount,
 			frequency,
-			number_of_installment
+			number_of_installment,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
 		));
 
 		let expected_instalment = InstalmentData {
@@ -140,14 +196,31 @@ fn subscribe_multiple_events() {
 			remaining_payments: number_of_installment,
 			beneficiary: PAUL(),
 			payer: CHARLIE(),
+			scheduled_at: 2,
+			status: SubscriptionStatus::Active,
+			failed_attempts: 0,
+			next_due: 2,
+			prepaid: false,
+			deposit: 0,
+			asset: None,
+			existence_policy: ExistencePolicy::KeepAlive,
+			total_paid: 0,
+			refunded: 0,
+			plan_id: None,
+			price_migration: None,
 		};
-		assert!(PalletSubscription::subscriptions(2).contains(&expected_instalment));
+		assert!(PalletSubscription::subscriptions(2).contains(&1));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(1),
+			Some(expected_instalment.clone())
+		);
 
 		let expected_event =
-			Event::PalletSubscription(crate::Event::Subscription(expected_instalment));
+			Event::PalletSubscription(crate::Event::Subscription(1, expected_instalment));
 		let received_event = &System::events()[1].event;
 
 		assert_eq!(*received_event, expected_event);
+		assert_eq!(PalletSubscription::subscription_nonce(), 2);
 	})
 }
 
@@ -164,7 +237,11 @@ fn subscribe_frequency_zero() {
 				BOB(),
 				amount,
 				frequency,
-				number_of_installment
+				number_of_installment,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
 			),
 			Error::<TestRuntime>::InvalidSubscription
 		);
@@ -184,7 +261,11 @@ fn subscribe_amount_zero() {
 				BOB(),
 				amount,
 				frequency,
-				number_of_installment
+				number_of_installment,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
 			),
 			Error::<TestRuntime>::InvalidSubscription
 		);
@@ -204,7 +285,11 @@ fn subscribe_amount_frequency_zero() {
 				BOB(),
 				amount,
 				frequency,
-				number_of_installment
+				number_of_installment,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
 			),
 			Error::<TestRuntime>::InvalidSubscription
 		);
@@ -223,7 +308,11 @@ fn subscribe_number_of_installment_none() {
 			BOB(),
 			amount,
 			frequency,
-			number_of_installment
+			number_of_installment,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
 		));
 
 		let expected_instalment = InstalmentData {
@@ -232,13 +321,636 @@ fn subscribe_number_of_installment_none() {
 			remaining_payments: number_of_installment,
 			beneficiary: BOB(),
 			payer: ALICE(),
+			scheduled_at: 2,
+			status: SubscriptionStatus::Active,
+			failed_attempts: 0,
+			next_due: 2,
+			prepaid: false,
+			deposit: 0,
+			asset: None,
+			existence_policy: ExistencePolicy::KeepAlive,
+			total_paid: 0,
+			refunded: 0,
+			plan_id: None,
+			price_migration: None,
 		};
-		assert!(PalletSubscription::subscriptions(2).contains(&expected_instalment));
+		assert!(PalletSubscription::subscriptions(2).contains(&0));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0),
+			Some(expected_instalment.clone())
+		);
 
 		let expected_event =
-			Event::PalletSubscription(crate::Event::Subscription(expected_instalment));
+			Event::PalletSubscription(crate::Event::Subscription(0, expected_instalment));
 		let received_event = &System::events()[0].event;
 
 		assert_eq!(*received_event, expected_event);
 	})
 }
+
+#[test]
+fn subscription_indexes() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			4000,
+			5,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			CHARLIE(),
+			4000,
+			5,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(PAUL()),
+			BOB(),
+			4000,
+			5,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		assert_eq!(
+			PalletSubscription::subscriptions_by_payer(ALICE()).to_vec(),
+			vec![0, 1]
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_payer(PAUL()).to_vec(),
+			vec![2]
+		);
+		assert!(PalletSubscription::subscriptions_by_payer(BOB()).is_empty());
+
+		assert_eq!(
+			PalletSubscription::subscriptions_by_beneficiary(BOB()).to_vec(),
+			vec![0, 2]
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_beneficiary(CHARLIE()).to_vec(),
+			vec![1]
+		);
+		assert!(PalletSubscription::subscriptions_by_beneficiary(ALICE()).is_empty());
+	})
+}
+
+#[test]
+fn subscription_indexes_are_not_capped() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		// More than fit in a block, the indexes don't limit how many customers BOB can have
+		let count = MaxSubscriptionsPerBlock::get() as u64 * 4;
+		for _ in 0..count {
+			assert_ok!(PalletSubscription::subscribe(
+				Origin::signed(ALICE()),
+				BOB(),
+				4000,
+				5,
+				None,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
+			));
+		}
+
+		let expected: Vec<u64> = (0..count).collect();
+		assert_eq!(
+			PalletSubscription::subscriptions_by_payer(ALICE()),
+			expected
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_beneficiary(BOB()),
+			expected
+		);
+	})
+}
+
+#[test]
+fn subscribe_full_block_overflows() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		for _ in 0..MaxSubscriptionsPerBlock::get() {
+			assert_ok!(PalletSubscription::subscribe(
+				Origin::signed(ALICE()),
+				BOB(),
+				4000,
+				5,
+				None,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
+			));
+		}
+		assert_eq!(
+			PalletSubscription::subscriptions(2).len(),
+			MaxSubscriptionsPerBlock::get() as usize
+		);
+
+		// Block 2 is full, the next subscription lands in block 3
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(CHARLIE()),
+			PAUL(),
+			4000,
+			5,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		let subscription_id = MaxSubscriptionsPerBlock::get() as u64;
+		assert_eq!(
+			PalletSubscription::subscriptions(3).to_vec(),
+			vec![subscription_id]
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(subscription_id).unwrap().scheduled_at,
+			3
+		);
+
+		// Every instalment still gets paid
+		let paul_balance_before = Balances::free_balance(&PAUL());
+		run_to_block(3);
+		assert_eq!(Balances::free_balance(&PAUL()), paul_balance_before + 4000);
+	})
+}
+
+#[test]
+fn subscribe_schedule_full() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		MaxScheduleLookahead::set(&1);
+
+		// Blocks 2 and 3 are full
+		for _ in 0..MaxSubscriptionsPerBlock::get() * 2 {
+			assert_ok!(PalletSubscription::subscribe(
+				Origin::signed(ALICE()),
+				BOB(),
+				4000,
+				5,
+				None,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
+			));
+		}
+		assert_eq!(
+			PalletSubscription::subscriptions(3).len(),
+			MaxSubscriptionsPerBlock::get() as usize
+		);
+
+		assert_noop!(
+			PalletSubscription::subscribe(
+				Origin::signed(CHARLIE()),
+				PAUL(),
+				4000,
+				5,
+				None,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
+			),
+			Error::<TestRuntime>::ScheduleFull
+		);
+
+		// Starting later leaves room
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(CHARLIE()),
+			PAUL(),
+			4000,
+			5,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			Some(4),
+			false
+		));
+	})
+}
+
+#[test]
+fn subscribe_prepaid_reserves_instalments() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+		let frequency = 5;
+		let alice_balance_before = Balances::free_balance(&ALICE());
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			frequency,
+			Some(3),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			true
+		));
+		assert!(PalletSubscription::subscriptions_by_id(0).unwrap().prepaid);
+		assert_eq!(Balances::reserved_balance(&ALICE()), amount * 3);
+		assert_eq!(
+			Balances::free_balance(&ALICE()),
+			alice_balance_before - amount * 3
+		);
+
+		// Instalments are paid out of the reserve
+		let bob_balance_before = Balances::free_balance(&BOB());
+		run_to_block(2);
+		assert_eq!(Balances::reserved_balance(&ALICE()), amount * 2);
+		assert_eq!(
+			Balances::free_balance(&ALICE()),
+			alice_balance_before - amount * 3
+		);
+		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before + amount);
+
+		run_to_block(2 + frequency * 2);
+		assert_eq!(Balances::reserved_balance(&ALICE()), 0);
+		assert_eq!(
+			Balances::free_balance(&ALICE()),
+			alice_balance_before - amount * 3
+		);
+		assert_eq!(
+			Balances::free_balance(&BOB()),
+			bob_balance_before + amount * 3
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().status,
+			SubscriptionStatus::Completed
+		);
+	})
+}
+
+#[test]
+fn unsubscribe_prepaid_refunds_reserve() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+		let alice_balance_before = Balances::free_balance(&ALICE());
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			5,
+			Some(3),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			true
+		));
+		run_to_block(2);
+
+		assert_ok!(PalletSubscription::unsubscribe_by_id(
+			Origin::signed(ALICE()),
+			0
+		));
+		assert_eq!(Balances::reserved_balance(&ALICE()), 0);
+		assert_eq!(
+			Balances::free_balance(&ALICE()),
+			alice_balance_before - amount
+		);
+	})
+}
+
+#[test]
+fn subscribe_prepaid_insufficient_balance() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = Balances::free_balance(&ALICE()) / 2;
+
+		assert_noop!(
+			PalletSubscription::subscribe(
+				Origin::signed(ALICE()),
+				BOB(),
+				amount,
+				5,
+				Some(3),
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				true
+			),
+			pallet_balances::Error::<TestRuntime>::InsufficientBalance
+		);
+		assert_noop!(
+			PalletSubscription::subscribe(
+				Origin::signed(ALICE()),
+				BOB(),
+				4000,
+				5,
+				Some(0),
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				true
+			),
+			Error::<TestRuntime>::InvalidSubscription
+		);
+	})
+}
+
+#[test]
+fn subscribe_prepaid_invalid_terms() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		// The reserve needs a number of instalments
+		assert_noop!(
+			PalletSubscription::subscribe(
+				Origin::signed(ALICE()),
+				BOB(),
+				4000,
+				5,
+				None,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				true
+			),
+			Error::<TestRuntime>::InvalidSubscription
+		);
+		// Only the native currency can be reserved
+		assert_noop!(
+			PalletSubscription::subscribe(
+				Origin::signed(ALICE()),
+				BOB(),
+				4000,
+				5,
+				Some(3),
+				Some(USD),
+				ExistencePolicy::KeepAlive,
+				None,
+				true
+			),
+			Error::<TestRuntime>::PrepaidInAsset
+		);
+	})
+}
+
+#[test]
+fn prepaid_partial_payment_is_rolled_back() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			5,
+			Some(3),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			true
+		));
+
+		// The reserve no longer covers an instalment and the free balance can't pay the rest
+		Balances::unreserve(&ALICE(), amount * 3 - 1000);
+		let alice_free_balance = Balances::free_balance(&ALICE());
+		assert_ok!(Balances::transfer(
+			Origin::signed(ALICE()),
+			CHARLIE(),
+			alice_free_balance - ExistentialDeposit::get()
+		));
+
+		let bob_balance_before = Balances::free_balance(&BOB());
+		run_to_block(2);
+		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before);
+		assert_eq!(Balances::reserved_balance(&ALICE()), 1000);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().status,
+			SubscriptionStatus::Delinquent
+		);
+	})
+}
+
+#[test]
+fn subscribe_reserves_deposit() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		SubscriptionDeposit::set(&1000);
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			4000,
+			5,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+		assert_eq!(Balances::reserved_balance(&ALICE()), 1000);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().deposit,
+			1000
+		);
+
+		// Kept on unsubscribe, given back on reap
+		assert_ok!(PalletSubscription::unsubscribe_by_id(
+			Origin::signed(ALICE()),
+			0
+		));
+		assert_eq!(Balances::reserved_balance(&ALICE()), 1000);
+
+		run_to_block(1 + ArchivePeriod::get());
+		assert_ok!(PalletSubscription::reap(Origin::signed(ALICE()), 0));
+		assert_eq!(Balances::reserved_balance(&ALICE()), 0);
+	})
+}
+
+#[test]
+fn deposit_released_on_reap() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		SubscriptionDeposit::set(&1000);
+		let frequency = 5;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			4000,
+			frequency,
+			Some(2),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			CHARLIE(),
+			4000,
+			frequency,
+			Some(2),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			true
+		));
+		assert_eq!(Balances::reserved_balance(&ALICE()), 1000 * 2 + 4000 * 2);
+
+		// The deposits are kept along with the archived subscriptions
+		run_to_block(2 + frequency);
+		assert_eq!(Balances::reserved_balance(&ALICE()), 1000 * 2);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().deposit,
+			1000
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(1).unwrap().deposit,
+			1000
+		);
+
+		run_to_block(2 + frequency + ArchivePeriod::get());
+		assert_ok!(PalletSubscription::reap(Origin::signed(ALICE()), 0));
+		assert_ok!(PalletSubscription::reap(Origin::signed(CHARLIE()), 1));
+		assert_eq!(Balances::reserved_balance(&ALICE()), 0);
+	})
+}
+
+#[test]
+fn deposit_kept_on_cancellation() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		SubscriptionDeposit::set(&1000);
+		let frequency = 5;
+		let amount = Balances::free_balance(&ALICE()) / 2 + 1;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			frequency,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+		assert_eq!(Balances::reserved_balance(&ALICE()), 1000);
+
+		// The second instalment fails, then every retry
+		let cancelled_at = 2 + frequency + RetryInterval::get() * MaxRetries::get() as u64;
+		run_to_block(cancelled_at);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().status,
+			SubscriptionStatus::Cancelled
+		);
+		assert_eq!(Balances::reserved_balance(&ALICE()), 1000);
+
+		run_to_block(cancelled_at + ArchivePeriod::get());
+		assert_ok!(PalletSubscription::reap(Origin::signed(ALICE()), 0));
+		assert_eq!(Balances::reserved_balance(&ALICE()), 0);
+	})
+}
+
+#[test]
+fn subscribe_in_asset() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+		let frequency = 5;
+		let alice_balance_before = Balances::free_balance(&ALICE());
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			frequency,
+			None,
+			Some(USD),
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().asset,
+			Some(USD)
+		);
+
+		// Paid in the asset, the native balance is left alone
+		run_to_block(2 + frequency);
+		assert_eq!(Assets::balance(USD, ALICE()), 1_000_000 - amount * 2);
+		assert_eq!(Assets::balance(USD, BOB()), amount * 2);
+		assert_eq!(Balances::free_balance(&ALICE()), alice_balance_before);
+	})
+}
+
+#[test]
+fn subscribe_starting_later() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let start_at = 50;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			4000,
+			5,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			Some(start_at),
+			false
+		));
+		assert!(PalletSubscription::subscriptions(2).is_empty());
+		assert!(PalletSubscription::subscriptions(start_at).contains(&0));
+
+		let subscription = PalletSubscription::subscriptions_by_id(0).unwrap();
+		assert_eq!(subscription.scheduled_at, start_at);
+		assert_eq!(subscription.next_due, start_at);
+
+		let bob_balance_before = Balances::free_balance(&BOB());
+		run_to_block(start_at - 1);
+		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before);
+		run_to_block(start_at);
+		assert_eq!(Balances::free_balance(&BOB()), bob_balance_before + 4000);
+	})
+}
+
+#[test]
+fn subscribe_invalid_start() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		run_to_block(10);
+
+		for start_at in [5, 10] {
+			assert_noop!(
+				PalletSubscription::subscribe(
+					Origin::signed(ALICE()),
+					BOB(),
+					4000,
+					5,
+					None,
+					None,
+					ExistencePolicy::KeepAlive,
+					Some(start_at),
+					false
+				),
+				Error::<TestRuntime>::StartInThePast
+			);
+		}
+
+		assert_noop!(
+			PalletSubscription::subscribe(
+				Origin::signed(ALICE()),
+				BOB(),
+				4000,
+				5,
+				None,
+				None,
+				ExistencePolicy::KeepAlive,
+				Some(10 + MaxStartDelay::get() + 1),
+				false
+			),
+			Error::<TestRuntime>::StartTooFarAhead
+		);
+	})
+}
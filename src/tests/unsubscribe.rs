@@ -1,6 +1,6 @@
 use super::mock::*;
-use crate::{Error, InstalmentData};
-use frame_support::{assert_noop, assert_ok};
+use crate::{Error, ExistencePolicy, InstalmentData, SubscriptionStatus};
+use frame_support::{assert_noop, assert_ok, traits::Get};
 
 #[test]
 fn unsubscribe() {
@@ -15,7 +15,11 @@ fn unsubscribe() {
 			BOB(),
 			amount,
 			frequency,
-			number_of_installment
+			number_of_installment,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
 		));
 
 		let expected_instalment = InstalmentData {
@@ -24,11 +28,27 @@ fn unsubscribe() {
 			remaining_payments: number_of_installment,
 			beneficiary: BOB(),
 			payer: ALICE(),
+			scheduled_at: 2,
+			status: SubscriptionStatus::Active,
+			failed_attempts: 0,
+			next_due: 2,
+			prepaid: false,
+			deposit: 0,
+			asset: None,
+			existence_policy: ExistencePolicy::KeepAlive,
+			total_paid: 0,
+			refunded: 0,
+			plan_id: None,
+			price_migration: None,
 		};
-		assert!(PalletSubscription::subscriptions(2).contains(&expected_instalment));
+		assert!(PalletSubscription::subscriptions(2).contains(&0));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0),
+			Some(expected_instalment.clone())
+		);
 
 		let expected_event =
-			Event::PalletSubscription(crate::Event::Subscription(expected_instalment));
+			Event::PalletSubscription(crate::Event::Subscription(0, expected_instalment));
 		let received_event = &System::events()[0].event;
 
 		assert_eq!(*received_event, expected_event);
@@ -52,15 +72,60 @@ fn unsubscribe() {
 			remaining_payments,
 			beneficiary: BOB(),
 			payer: ALICE(),
+			scheduled_at: 2,
+			status: SubscriptionStatus::Active,
+			failed_attempts: 0,
+			next_due: 2,
+			prepaid: false,
+			deposit: 0,
+			asset: None,
+			existence_policy: ExistencePolicy::KeepAlive,
+			total_paid: 0,
+			refunded: 0,
+			plan_id: None,
+			price_migration: None,
 		};
-		assert!(!PalletSubscription::subscriptions(when).contains(&subscription));
-		let expected_event = Event::PalletSubscription(crate::Event::Unsubscription(subscription));
+		assert!(!PalletSubscription::subscriptions(when).contains(&0));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().status,
+			SubscriptionStatus::Cancelled
+		);
+		let expected_event =
+			Event::PalletSubscription(crate::Event::Unsubscription(0, subscription));
 		let received_event = &System::events()[1].event;
 
 		assert_eq!(*received_event, expected_event);
 	})
 }
 
+#[test]
+fn unsubscribe_keeps_order() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		for _ in 0..3 {
+			assert_ok!(PalletSubscription::subscribe(
+				Origin::signed(ALICE()),
+				BOB(),
+				4000,
+				5,
+				None,
+				None,
+				ExistencePolicy::KeepAlive,
+				None,
+				false
+			));
+		}
+		assert_eq!(PalletSubscription::subscriptions(2).to_vec(), vec![0, 1, 2]);
+
+		// The instalments left are still executed first come first served
+		assert_ok!(PalletSubscription::unsubscribe(
+			Origin::signed(ALICE()),
+			2,
+			0
+		));
+		assert_eq!(PalletSubscription::subscriptions(2).to_vec(), vec![1, 2]);
+	})
+}
+
 #[test]
 fn unsubscribe_no_subscriptions_found() {
 	ExternalityBuilder::default().build().execute_with(|| {
@@ -89,7 +154,11 @@ fn unsubscribe_invalid_when() {
 			BOB(),
 			amount,
 			frequency,
-			number_of_installment
+			number_of_installment,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
 		));
 
 		let expected_instalment = InstalmentData {
@@ -98,11 +167,27 @@ fn unsubscribe_invalid_when() {
 			remaining_payments: number_of_installment,
 			beneficiary: BOB(),
 			payer: ALICE(),
+			scheduled_at: 2,
+			status: SubscriptionStatus::Active,
+			failed_attempts: 0,
+			next_due: 2,
+			prepaid: false,
+			deposit: 0,
+			asset: None,
+			existence_policy: ExistencePolicy::KeepAlive,
+			total_paid: 0,
+			refunded: 0,
+			plan_id: None,
+			price_migration: None,
 		};
-		assert!(PalletSubscription::subscriptions(2).contains(&expected_instalment));
+		assert!(PalletSubscription::subscriptions(2).contains(&0));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0),
+			Some(expected_instalment.clone())
+		);
 
 		let expected_event =
-			Event::PalletSubscription(crate::Event::Subscription(expected_instalment));
+			Event::PalletSubscription(crate::Event::Subscription(0, expected_instalment));
 		let received_event = &System::events()[0].event;
 
 		assert_eq!(*received_event, expected_event);
@@ -133,7 +218,11 @@ fn unsubscribe_index_out_of_bounds() {
 			BOB(),
 			amount,
 			frequency,
-			number_of_installment
+			number_of_installment,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
 		));
 
 		let expected_instalment = InstalmentData {
@@ -142,10 +231,26 @@ fn unsubscribe_index_out_of_bounds() {
 			remaining_payments: number_of_installment,
 			beneficiary: BOB(),
 			payer: ALICE(),
+			scheduled_at: 2,
+			status: SubscriptionStatus::Active,
+			failed_attempts: 0,
+			next_due: 2,
+			prepaid: false,
+			deposit: 0,
+			asset: None,
+			existence_policy: ExistencePolicy::KeepAlive,
+			total_paid: 0,
+			refunded: 0,
+			plan_id: None,
+			price_migration: None,
 		};
-		assert!(PalletSubscription::subscriptions(2).contains(&expected_instalment));
+		assert!(PalletSubscription::subscriptions(2).contains(&0));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0),
+			Some(expected_instalment.clone())
+		);
 		let expected_event =
-			Event::PalletSubscription(crate::Event::Subscription(expected_instalment));
+			Event::PalletSubscription(crate::Event::Subscription(0, expected_instalment));
 		let received_event = &System::events()[0].event;
 
 		assert_eq!(*received_event, expected_event);
@@ -176,7 +281,11 @@ fn unsubscribe_wrong_subscriber() {
 			BOB(),
 			amount,
 			frequency,
-			number_of_installment
+			number_of_installment,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
 		));
 		let expected_instalment = InstalmentData {
 			frequency,
@@ -184,11 +293,27 @@ fn unsubscribe_wrong_subscriber() {
 			remaining_payments: number_of_installment,
 			beneficiary: BOB(),
 			payer: ALICE(),
+			scheduled_at: 2,
+			status: SubscriptionStatus::Active,
+			failed_attempts: 0,
+			next_due: 2,
+			prepaid: false,
+			deposit: 0,
+			asset: None,
+			existence_policy: ExistencePolicy::KeepAlive,
+			total_paid: 0,
+			refunded: 0,
+			plan_id: None,
+			price_migration: None,
 		};
-		assert!(PalletSubscription::subscriptions(2).contains(&expected_instalment));
+		assert!(PalletSubscription::subscriptions(2).contains(&0));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0),
+			Some(expected_instalment.clone())
+		);
 
 		let expected_event =
-			Event::PalletSubscription(crate::Event::Subscription(expected_instalment));
+			Event::PalletSubscription(crate::Event::Subscription(0, expected_instalment));
 		let received_event = &System::events()[0].event;
 
 		assert_eq!(*received_event, expected_event);
@@ -204,3 +329,365 @@ fn unsubscribe_wrong_subscriber() {
 		);
 	})
 }
+
+#[test]
+fn unsubscribe_by_id() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+		let frequency = 5;
+		let number_of_installment = Some(4);
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			frequency,
+			number_of_installment,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(CHARLIE()),
+			BOB(),
+			amount,
+			frequency,
+			number_of_installment,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		// The first instalment moves ALICE's subscription to another block
+		run_to_block(2);
+		assert!(PalletSubscription::subscriptions(2 + frequency).contains(&0));
+
+		assert_ok!(PalletSubscription::unsubscribe_by_id(
+			Origin::signed(ALICE()),
+			0
+		));
+
+		let subscription = InstalmentData {
+			frequency,
+			amount,
+			remaining_payments: Some(3),
+			beneficiary: BOB(),
+			payer: ALICE(),
+			scheduled_at: 2 + frequency,
+			status: SubscriptionStatus::Active,
+			failed_attempts: 0,
+			next_due: 2 + frequency,
+			prepaid: false,
+			deposit: 0,
+			asset: None,
+			existence_policy: ExistencePolicy::KeepAlive,
+			total_paid: amount,
+			refunded: 0,
+			plan_id: None,
+			price_migration: None,
+		};
+		assert_eq!(PalletSubscription::subscriptions(2 + frequency), vec![1]);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().status,
+			SubscriptionStatus::Cancelled
+		);
+		assert!(PalletSubscription::subscriptions_by_payer(ALICE()).is_empty());
+		assert_eq!(
+			PalletSubscription::subscriptions_by_beneficiary(BOB()).to_vec(),
+			vec![1]
+		);
+
+		let expected_event =
+			Event::PalletSubscription(crate::Event::Unsubscription(0, subscription));
+		let received_event = &System::events().last().unwrap().event;
+
+		assert_eq!(*received_event, expected_event);
+	})
+}
+
+#[test]
+fn unsubscribe_by_id_unknown_subscription() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_noop!(
+			PalletSubscription::unsubscribe_by_id(Origin::signed(ALICE()), 0),
+			Error::<TestRuntime>::UnknownSubscription
+		);
+	})
+}
+
+#[test]
+fn unsubscribe_by_id_wrong_subscriber() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			4000,
+			5,
+			Some(4),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		assert_noop!(
+			PalletSubscription::unsubscribe_by_id(Origin::signed(CHARLIE()), 0),
+			Error::<TestRuntime>::CallerIsNotSubscriber
+		);
+	})
+}
+
+#[test]
+fn unsubscribe_by_id_completed_subscription() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			4000,
+			5,
+			Some(1),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		run_to_block(2);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().status,
+			SubscriptionStatus::Completed
+		);
+
+		assert_noop!(
+			PalletSubscription::unsubscribe_by_id(Origin::signed(ALICE()), 0),
+			Error::<TestRuntime>::SubscriptionIsCompleted
+		);
+	})
+}
+
+#[test]
+fn reap() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			4000,
+			5,
+			Some(1),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		assert_noop!(
+			PalletSubscription::reap(Origin::signed(ALICE()), 0),
+			Error::<TestRuntime>::SubscriptionIsNotOver
+		);
+
+		// The record is kept for a while once over
+		run_to_block(2);
+		assert_noop!(
+			PalletSubscription::reap(Origin::signed(ALICE()), 0),
+			Error::<TestRuntime>::ArchivePeriodNotOver
+		);
+
+		run_to_block(2 + ArchivePeriod::get());
+		assert_noop!(
+			PalletSubscription::reap(Origin::signed(CHARLIE()), 0),
+			Error::<TestRuntime>::CallerIsNotParty
+		);
+		assert_ok!(PalletSubscription::reap(Origin::signed(BOB()), 0));
+		assert_eq!(PalletSubscription::subscriptions_by_id(0), None);
+
+		let expected_event =
+			Event::PalletSubscription(crate::Event::SubscriptionReaped { subscription_id: 0 });
+		assert_eq!(System::events().last().unwrap().event, expected_event);
+	})
+}
+
+#[test]
+fn unsubscribe_by_beneficiary() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			4000,
+			5,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		assert_ok!(PalletSubscription::unsubscribe_by_id(
+			Origin::signed(BOB()),
+			0
+		));
+		assert!(PalletSubscription::subscriptions(2).is_empty());
+		assert!(PalletSubscription::subscriptions_by_payer(ALICE()).is_empty());
+
+		// The record is kept for refunds
+		let subscription = PalletSubscription::subscriptions_by_id(0).unwrap();
+		assert_eq!(subscription.status, SubscriptionStatus::Terminated);
+		assert_eq!(subscription.scheduled_at, 1);
+
+		let expected_event = Event::PalletSubscription(crate::Event::SubscriptionTerminated {
+			subscription_id: 0,
+			payer: ALICE(),
+			beneficiary: BOB(),
+		});
+		assert_eq!(System::events().last().unwrap().event, expected_event);
+
+		assert_noop!(
+			PalletSubscription::unsubscribe_by_id(Origin::signed(ALICE()), 0),
+			Error::<TestRuntime>::SubscriptionIsTerminated
+		);
+		assert_noop!(
+			PalletSubscription::execute_overdue(Origin::signed(CHARLIE()), 0),
+			Error::<TestRuntime>::SubscriptionIsTerminated
+		);
+	})
+}
+
+#[test]
+fn refund_terminated_subscription() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		SubscriptionDeposit::set(&1000);
+		let amount = 4000;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			5,
+			Some(3),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			true
+		));
+		run_to_block(2);
+
+		// Terminating gives back what is left of the prepaid reserve, the deposit stays
+		assert_ok!(PalletSubscription::unsubscribe_by_id(
+			Origin::signed(BOB()),
+			0
+		));
+		assert_eq!(Balances::reserved_balance(&ALICE()), 1000);
+
+		let alice_balance_before = Balances::free_balance(&ALICE());
+		assert_ok!(PalletSubscription::refund(Origin::signed(BOB()), 0, amount));
+		assert_eq!(
+			Balances::free_balance(&ALICE()),
+			alice_balance_before + amount
+		);
+
+		run_to_block(2 + ArchivePeriod::get());
+		assert_ok!(PalletSubscription::reap(Origin::signed(ALICE()), 0));
+		assert_eq!(Balances::reserved_balance(&ALICE()), 0);
+		assert_eq!(PalletSubscription::subscriptions_by_id(0), None);
+	})
+}
+
+#[test]
+fn refund() {
+	ExternalityBuilder::default().build().execute_with(|| {
+		let amount = 4000;
+		let frequency = 5;
+
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(ALICE()),
+			BOB(),
+			amount,
+			frequency,
+			Some(2),
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+
+		// Nothing has been paid yet
+		assert_noop!(
+			PalletSubscription::refund(Origin::signed(BOB()), 0, 1),
+			Error::<TestRuntime>::RefundExceedsPayments
+		);
+
+		// The subscription can still be refunded once completed
+		run_to_block(2 + frequency);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().total_paid,
+			amount * 2
+		);
+
+		assert_noop!(
+			PalletSubscription::refund(Origin::signed(ALICE()), 0, amount),
+			Error::<TestRuntime>::CallerIsNotBeneficiary
+		);
+
+		let alice_balance_before = Balances::free_balance(&ALICE());
+		assert_ok!(PalletSubscription::refund(Origin::signed(BOB()), 0, amount));
+		assert_eq!(
+			Balances::free_balance(&ALICE()),
+			alice_balance_before + amount
+		);
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(0).unwrap().refunded,
+			amount
+		);
+		assert_eq!(
+			System::events().last().unwrap().event,
+			Event::PalletSubscription(crate::Event::Refunded {
+				subscription_id: 0,
+				amount
+			})
+		);
+
+		assert_noop!(
+			PalletSubscription::refund(Origin::signed(BOB()), 0, amount + 1),
+			Error::<TestRuntime>::RefundExceedsPayments
+		);
+		assert_noop!(
+			PalletSubscription::refund(Origin::signed(BOB()), 0, 0),
+			Error::<TestRuntime>::RefundIsZero
+		);
+		assert_ok!(PalletSubscription::refund(Origin::signed(BOB()), 0, amount));
+
+		// A subscription the payer cancelled can be refunded as well
+		assert_ok!(PalletSubscription::subscribe(
+			Origin::signed(CHARLIE()),
+			BOB(),
+			amount,
+			frequency,
+			None,
+			None,
+			ExistencePolicy::KeepAlive,
+			None,
+			false
+		));
+		run_to_block(3 + frequency);
+		assert_ok!(PalletSubscription::unsubscribe_by_id(
+			Origin::signed(CHARLIE()),
+			1
+		));
+		assert_eq!(
+			PalletSubscription::subscriptions_by_id(1).unwrap().status,
+			SubscriptionStatus::Cancelled
+		);
+
+		let charlie_balance_before = Balances::free_balance(&CHARLIE());
+		assert_ok!(PalletSubscription::refund(Origin::signed(BOB()), 1, amount));
+		assert_eq!(
+			Balances::free_balance(&CHARLIE()),
+			charlie_balance_before + amount
+		);
+		assert_noop!(
+			PalletSubscription::unsubscribe_by_id(Origin::signed(CHARLIE()), 1),
+			Error::<TestRuntime>::SubscriptionIsCancelled
+		);
+	})
+}
@@ -6,15 +6,151 @@ use scale_info::TypeInfo;
 pub type BalanceOf<T> =
 	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
+pub type AssetIdOf<T> =
+	<<T as Config>::Assets as fungibles::Inspect<<T as frame_system::Config>::AccountId>>::AssetId;
+
 pub type Nonce = u64;
 pub type PlanId = u64;
+pub type PriceMigrationId = u64;
 pub type SubscriptionId = u64;
 
+#[derive(Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq, Debug, MaxEncodedLen)]
+pub enum SubscriptionStatus {
+	/// Instalments are scheduled and executed normally
+	Active,
+	/// The last instalment could not be paid, it is scheduled again to be retried
+	Delinquent,
+	/// Every instalment has been paid, the subscription is kept as an archive until reaped
+	Completed,
+	/// The payer paused the subscription, it is not scheduled until they resume it
+	Paused,
+	/// The beneficiary terminated the subscription, it is kept as an archive until reaped
+	Terminated,
+	/// The payer unsubscribed, or the instalment failed `MaxRetries` times, it is kept as an
+	/// archive until reaped
+	Cancelled,
+}
+
+impl SubscriptionStatus {
+	/// Whether the subscription has no instalment left to execute
+	pub fn is_over(&self) -> bool {
+		matches!(
+			self,
+			SubscriptionStatus::Completed
+				| SubscriptionStatus::Terminated
+				| SubscriptionStatus::Cancelled
+		)
+	}
+}
+
+#[derive(Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq, Debug, MaxEncodedLen)]
+pub enum ExistencePolicy {
+	/// An instalment fails rather than taking the payer below the existential deposit
+	KeepAlive,
+	/// An instalment may take the payer below the existential deposit, reaping the account
+	AllowDeath,
+}
+
+impl Default for ExistencePolicy {
+	fn default() -> Self {
+		ExistencePolicy::KeepAlive
+	}
+}
+
+impl From<ExistencePolicy> for ExistenceRequirement {
+	fn from(policy: ExistencePolicy) -> Self {
+		match policy {
+			ExistencePolicy::KeepAlive => ExistenceRequirement::KeepAlive,
+			ExistencePolicy::AllowDeath => ExistenceRequirement::AllowDeath,
+		}
+	}
+}
+
 #[derive(Clone, Encode, Decode, TypeInfo, PartialEq, Eq, Debug, MaxEncodedLen)]
-pub struct InstalmentData<BlockNumber, Balance, AccountId> {
+pub struct InstalmentData<BlockNumber, Balance, AccountId, AssetId> {
 	pub frequency: BlockNumber,
 	pub amount: Balance,
 	pub remaining_payments: Option<u32>,
 	pub beneficiary: AccountId,
 	pub payer: AccountId,
+	/// Block the next instalment is scheduled in, or the block the subscription ended at once
+	/// it is over
+	pub scheduled_at: BlockNumber,
+	pub status: SubscriptionStatus,
+	pub failed_attempts: u32,
+	/// Block the next instalment is due at, instalments are scheduled from it so that deferring
+	/// one does not shift the following ones
+	pub next_due: BlockNumber,
+	/// The remaining instalments are reserved from the payer and paid out of the reserve
+	pub prepaid: bool,
+	/// Reserved from the payer for as long as the subscription is on record
+	pub deposit: Balance,
+	/// The asset instalments are paid in, the native currency when there is none
+	pub asset: Option<AssetId>,
+	pub existence_policy: ExistencePolicy,
+	/// Everything paid by the payer so far
+	pub total_paid: Balance,
+	/// What the beneficiary gave back to the payer out of `total_paid`
+	pub refunded: Balance,
+	/// The plan the subscription was made from, if any
+	pub plan_id: Option<PlanId>,
+	/// The last price migration of the plan the subscription went through, either by moving to
+	/// the new price or by agreeing on an amendment after it was decided
+	pub price_migration: Option<PriceMigrationId>,
+}
+
+/// The terms a new subscription is made with
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SubscriptionTerms<BlockNumber, Balance, AccountId, AssetId> {
+	pub beneficiary: AccountId,
+	pub amount: Balance,
+	pub frequency: BlockNumber,
+	pub remaining_payments: Option<u32>,
+	pub asset: Option<AssetId>,
+	pub existence_policy: ExistencePolicy,
+	/// Block the first instalment is due at
+	pub start_at: BlockNumber,
+	pub plan_id: Option<PlanId>,
+	pub prepaid: bool,
+}
+
+#[derive(Clone, Encode, Decode, TypeInfo, PartialEq, Eq, Debug, MaxEncodedLen)]
+pub struct Plan<BlockNumber, Balance, AccountId> {
+	pub frequency: BlockNumber,
+	pub amount: Balance,
+	pub remaining_payments: Option<u32>,
+	pub beneficiary: AccountId,
+	/// Number of blocks before the first instalment of a new subscriber, once per subscriber
+	pub trial: BlockNumber,
+	/// Reserved from the owner for as long as the plan exists
+	pub deposit: Balance,
+}
+
+/// New terms proposed by one party of a subscription, applied once the other one accepts them
+#[derive(Clone, Encode, Decode, TypeInfo, PartialEq, Eq, Debug, MaxEncodedLen)]
+pub struct Amendment<BlockNumber, Balance, AccountId> {
+	pub proposer: AccountId,
+	pub amount: Balance,
+	pub frequency: BlockNumber,
+	pub remaining_payments: Option<u32>,
+}
+
+/// How a plan price change applies to the existing subscribers
+#[derive(Clone, Copy, Encode, Decode, TypeInfo, PartialEq, Eq, Debug, MaxEncodedLen)]
+pub enum PriceUpdateMode {
+	/// Existing subscribers keep the price they subscribed at
+	Grandfather,
+	/// Existing subscribers pay the new price once the notice is over
+	Migrate,
+}
+
+#[derive(Clone, Encode, Decode, TypeInfo, PartialEq, Eq, Debug, MaxEncodedLen)]
+pub struct PriceMigration<BlockNumber, Balance> {
+	pub id: PriceMigrationId,
+	pub amount: Balance,
+	pub effective_at: BlockNumber,
+	/// Subscriptions with a lower id were made at the previous price
+	pub subscribed_before: SubscriptionId,
+	/// Subscriptions that still have to go through the migration, it is removed once none is left
+	pub pending: u32,
 }
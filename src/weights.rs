@@ -0,0 +1,375 @@
+//! Weights for pallet_subscription
+//!
+//! PLACEHOLDER WEIGHTS, these were not produced by `benchmark pallet`. The storage accesses are
+//! counted from the code, the times are coarse guesses rounded up to one significant digit and
+//! were never measured. Replace this file with the output of the benchmarks in `benchmarking.rs`
+//! before using the pallet on a production chain.
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use core::marker::PhantomData;
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight}};
+
+/// Weight functions needed for pallet_subscription.
+pub trait WeightInfo {
+	fn subscribe() -> Weight;
+	fn unsubscribe() -> Weight;
+	fn unsubscribe_by_id() -> Weight;
+	fn create_plan(m: u32, ) -> Weight;
+	fn subscribe_to_plan() -> Weight;
+	fn set_plan_metadata(m: u32, ) -> Weight;
+	fn on_initialize(s: u32, ) -> Weight;
+	fn schedule_subscription(l: u32, ) -> Weight;
+	fn execute_overdue() -> Weight;
+	fn pause() -> Weight;
+	fn resume() -> Weight;
+	fn refund() -> Weight;
+	fn propose_amendment() -> Weight;
+	fn accept_amendment() -> Weight;
+	fn update_plan_price() -> Weight;
+	fn reap() -> Weight;
+	fn remove_plan(t: u32, ) -> Weight;
+}
+
+/// Placeholder weights for pallet_subscription, see the module documentation.
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+	// Storage: PalletSubscription SubscriptionNonce (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsByPayer (r:0 w:1)
+	// Storage: PalletSubscription SubscriptionsByBeneficiary (r:0 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsById (r:0 w:1)
+	fn subscribe() -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(6 as Weight))
+	}
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsByPayer (r:0 w:1)
+	// Storage: PalletSubscription SubscriptionsByBeneficiary (r:0 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:1 w:1)
+	// Storage: PalletSubscription PlanPriceMigrations (r:1 w:1)
+	fn unsubscribe() -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsByPayer (r:0 w:1)
+	// Storage: PalletSubscription SubscriptionsByBeneficiary (r:0 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:1 w:1)
+	// Storage: PalletSubscription PlanPriceMigrations (r:1 w:1)
+	fn unsubscribe_by_id() -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(7 as Weight))
+	}
+	// Storage: PalletSubscription PlanNonce (r:1 w:1)
+	// Storage: PalletSubscription PlanMetadata (r:0 w:1)
+	// Storage: PalletSubscription Plans (r:0 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn create_plan(m: u32, ) -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add((2_000 as Weight).saturating_mul(m as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Storage: PalletSubscription Plans (r:1 w:0)
+	// Storage: PalletSubscription TrialsUsed (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionNonce (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsByPayer (r:0 w:1)
+	// Storage: PalletSubscription SubscriptionsByBeneficiary (r:0 w:1)
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsById (r:0 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:1 w:1)
+	fn subscribe_to_plan() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+	}
+	// Storage: PalletSubscription Plans (r:1 w:0)
+	// Storage: PalletSubscription PlanMetadata (r:0 w:1)
+	fn set_plan_metadata(m: u32, ) -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add((2_000 as Weight).saturating_mul(m as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: PalletSubscription Subscriptions (r:2 w:2)
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: PalletSubscription PlanPriceMigrations (r:1 w:1)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:1 w:1)
+	fn on_initialize(s: u32, ) -> Weight {
+		(5_000_000 as Weight)
+			.saturating_add((40_000_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((6 as Weight).saturating_mul(s as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((6 as Weight).saturating_mul(s as Weight)))
+	}
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	fn schedule_subscription(l: u32, ) -> Weight {
+		(4_000_000 as Weight)
+			.saturating_add((3_000_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: PalletSubscription IncompleteSince (r:1 w:0)
+	// Storage: PalletSubscription Subscriptions (r:2 w:2)
+	// Storage: System Account (r:3 w:3)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:1 w:1)
+	// Storage: PalletSubscription PlanPriceMigrations (r:1 w:1)
+	fn execute_overdue() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(9 as Weight))
+			.saturating_add(T::DbWeight::get().writes(8 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	fn pause() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	fn resume() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn refund() -> Weight {
+		(40_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(3 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:0)
+	// Storage: PalletSubscription Amendments (r:0 w:1)
+	fn propose_amendment() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: PalletSubscription Amendments (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PalletSubscription PlanPriceMigrations (r:1 w:1)
+	fn accept_amendment() -> Weight {
+		(40_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+	}
+	// Storage: PalletSubscription Plans (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionNonce (r:1 w:0)
+	// Storage: PalletSubscription PlanPriceMigrations (r:0 w:1)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:1 w:0)
+	// Storage: PalletSubscription PriceMigrationNonce (r:1 w:1)
+	fn update_plan_price() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn reap() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: PalletSubscription Plans (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PalletSubscription PlanMetadata (r:0 w:1)
+	// Storage: PalletSubscription PlanPriceMigrations (r:0 w:1)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:0 w:1)
+	// Storage: PalletSubscription TrialsUsed (r:0 w:1)
+	fn remove_plan(t: u32, ) -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add((2_000_000 as Weight).saturating_mul(t as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(t as Weight)))
+	}
+}
+
+// For backwards compatibility and tests
+impl WeightInfo for () {
+	// Storage: PalletSubscription SubscriptionNonce (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsByPayer (r:0 w:1)
+	// Storage: PalletSubscription SubscriptionsByBeneficiary (r:0 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsById (r:0 w:1)
+	fn subscribe() -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
+	}
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsByPayer (r:0 w:1)
+	// Storage: PalletSubscription SubscriptionsByBeneficiary (r:0 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:1 w:1)
+	// Storage: PalletSubscription PlanPriceMigrations (r:1 w:1)
+	fn unsubscribe() -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsByPayer (r:0 w:1)
+	// Storage: PalletSubscription SubscriptionsByBeneficiary (r:0 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:1 w:1)
+	// Storage: PalletSubscription PlanPriceMigrations (r:1 w:1)
+	fn unsubscribe_by_id() -> Weight {
+		(50_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(7 as Weight))
+	}
+	// Storage: PalletSubscription PlanNonce (r:1 w:1)
+	// Storage: PalletSubscription PlanMetadata (r:0 w:1)
+	// Storage: PalletSubscription Plans (r:0 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn create_plan(m: u32, ) -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add((2_000 as Weight).saturating_mul(m as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Storage: PalletSubscription Plans (r:1 w:0)
+	// Storage: PalletSubscription TrialsUsed (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionNonce (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsByPayer (r:0 w:1)
+	// Storage: PalletSubscription SubscriptionsByBeneficiary (r:0 w:1)
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionsById (r:0 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:1 w:1)
+	fn subscribe_to_plan() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
+	}
+	// Storage: PalletSubscription Plans (r:1 w:0)
+	// Storage: PalletSubscription PlanMetadata (r:0 w:1)
+	fn set_plan_metadata(m: u32, ) -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add((2_000 as Weight).saturating_mul(m as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: PalletSubscription Subscriptions (r:2 w:2)
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	// Storage: PalletSubscription PlanPriceMigrations (r:1 w:1)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:1 w:1)
+	fn on_initialize(s: u32, ) -> Weight {
+		(5_000_000 as Weight)
+			.saturating_add((40_000_000 as Weight).saturating_mul(s as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((6 as Weight).saturating_mul(s as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((6 as Weight).saturating_mul(s as Weight)))
+	}
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	fn schedule_subscription(l: u32, ) -> Weight {
+		(4_000_000 as Weight)
+			.saturating_add((3_000_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((1 as Weight).saturating_mul(l as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: PalletSubscription IncompleteSince (r:1 w:0)
+	// Storage: PalletSubscription Subscriptions (r:2 w:2)
+	// Storage: System Account (r:3 w:3)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:1 w:1)
+	// Storage: PalletSubscription PlanPriceMigrations (r:1 w:1)
+	fn execute_overdue() -> Weight {
+		(60_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(8 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	fn pause() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: PalletSubscription Subscriptions (r:1 w:1)
+	fn resume() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: System Account (r:2 w:2)
+	fn refund() -> Weight {
+		(40_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(3 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:0)
+	// Storage: PalletSubscription Amendments (r:0 w:1)
+	fn propose_amendment() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: PalletSubscription Amendments (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PalletSubscription PlanPriceMigrations (r:1 w:1)
+	fn accept_amendment() -> Weight {
+		(40_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+	}
+	// Storage: PalletSubscription Plans (r:1 w:1)
+	// Storage: PalletSubscription SubscriptionNonce (r:1 w:0)
+	// Storage: PalletSubscription PlanPriceMigrations (r:0 w:1)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:1 w:0)
+	// Storage: PalletSubscription PriceMigrationNonce (r:1 w:1)
+	fn update_plan_price() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	// Storage: PalletSubscription SubscriptionsById (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn reap() -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	// Storage: PalletSubscription Plans (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	// Storage: PalletSubscription PlanMetadata (r:0 w:1)
+	// Storage: PalletSubscription PlanPriceMigrations (r:0 w:1)
+	// Storage: PalletSubscription PlanSubscriptionCount (r:0 w:1)
+	// Storage: PalletSubscription TrialsUsed (r:0 w:1)
+	fn remove_plan(t: u32, ) -> Weight {
+		(30_000_000 as Weight)
+			.saturating_add((2_000_000 as Weight).saturating_mul(t as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(5 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((1 as Weight).saturating_mul(t as Weight)))
+	}
+}